@@ -1,18 +1,37 @@
 extern crate orbclient;
 
-use orbclient::{Color, Window, Renderer, EventOption, GraphicsPath};
+use orbclient::{Color, Window, WindowFlag, Renderer, EventOption, GraphicsPath};
 
 fn main() {
     let (width, height) = orbclient::get_display_size().unwrap();
 
-    let mut window = Window::new((width as i32)/4,
-                                 (height as i32)/4,
-                                 width/2,
-                                 height/2,
-                                 "TITLE")
+    let mut window = Window::new_flags((width as i32)/4,
+                                       (height as i32)/4,
+                                       width/2,
+                                       height/2,
+                                       "TITLE",
+                                       &[WindowFlag::Resizable])
                          .unwrap();
 
-    let (win_w, win_h) = (width/2, height/2);
+    draw(&mut window);
+
+    'events: loop {
+        for event in window.events() {
+            match event.to_option() {
+                EventOption::Quit(_quit_event) => break 'events,
+                EventOption::Resize(resize_event) => {
+                    println!("{:?}", resize_event);
+                    draw(&mut window);
+                },
+                event_option => println!("{:?}", event_option)
+            }
+        }
+    }
+}
+
+fn draw(window: &mut Window) {
+    let (win_w, win_h) = (window.width(), window.height());
+    window.clear();
     // top left -> bottom rigth
     window.linear_gradient(0, 0, win_w/3, win_h, 0, 0,  (win_w/3) as i32, (win_h/2) as i32, Color::rgb(128,128,128), Color::rgb(255,255,255));
     // horizontal gradient
@@ -57,13 +76,4 @@ fn main() {
     window.char(208, 200, '═', Color::rgb(0, 0, 0));
 
     window.sync();
-
-    'events: loop {
-        for event in window.events() {
-            match event.to_option() {
-                EventOption::Quit(_quit_event) => break 'events,
-                event_option => println!("{:?}", event_option)
-            }
-        }
-    }
 }
@@ -0,0 +1,310 @@
+use std::fs::File;
+use std::io::{self, Write};
+
+use color::Color;
+#[cfg(feature = "png")]
+use png;
+
+/// Size of the file header and BITMAPINFOHEADER written before the pixels
+const HEADER_SIZE: usize = 14 + 40;
+
+/// Uncompressed pixels
+const BI_RGB: u32 = 0;
+/// Uncompressed pixels with channel masks after the header
+const BI_BITFIELDS: u32 = 3;
+
+/// A bitmap image
+pub struct BmpFile {
+    /// The width of the image
+    w: u32,
+    /// The height of the image
+    h: u32,
+    /// The image data
+    data: Vec<Color>,
+}
+
+impl BmpFile {
+    /// Create a new bitmap from pixel data, row by row from the top left
+    pub fn new(w: u32, h: u32, data: Vec<Color>) -> Self {
+        BmpFile {
+            w: w,
+            h: h,
+            data: data,
+        }
+    }
+
+    /// Parse a BMP from memory, such as one embedded with `include_bytes!`. Returns `None` if the data is not an uncompressed 24-bit or 32-bit BMP
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < HEADER_SIZE || &bytes[0..2] != b"BM" {
+            return None;
+        }
+
+        let offset = read_u32(bytes, 10) as usize;
+        let header_size = read_u32(bytes, 14);
+        let width = read_u32(bytes, 18) as i32;
+        let height = read_u32(bytes, 22) as i32;
+        let bits = read_u16(bytes, 28);
+        let compression = read_u32(bytes, 30);
+        if width <= 0 || height == 0 || height == i32::min_value() {
+            return None;
+        }
+
+        // Channel masks as (red, green, blue, alpha)
+        let masks = match (bits, compression) {
+            (24, BI_RGB) => (0xFF0000, 0xFF00, 0xFF, 0),
+            (32, BI_RGB) => (0xFF0000, 0xFF00, 0xFF, 0xFF000000),
+            (32, BI_BITFIELDS) if bytes.len() >= HEADER_SIZE + 12 => (
+                read_u32(bytes, HEADER_SIZE),
+                read_u32(bytes, HEADER_SIZE + 4),
+                read_u32(bytes, HEADER_SIZE + 8),
+                // Only the V3 header and later have an alpha mask
+                if header_size >= 56 && bytes.len() >= HEADER_SIZE + 16 { read_u32(bytes, HEADER_SIZE + 12) } else { 0 }
+            ),
+            _ => return None
+        };
+
+        // A negative height means the rows are stored top-down
+        let w = width as u32;
+        let h = height.abs() as u32;
+        let top_down = height < 0;
+
+        // Rows are padded to a multiple of 4 bytes
+        let pixel_size = bits as usize / 8;
+        let row_size = (w as usize * pixel_size + 3) / 4 * 4;
+        let end = row_size.checked_mul(h as usize).and_then(|size| size.checked_add(offset));
+        if end.map_or(true, |end| end > bytes.len()) {
+            return None;
+        }
+
+        let mut data = Vec::with_capacity(w as usize * h as usize);
+        for y in 0..h as usize {
+            let row = if top_down { y } else { h as usize - 1 - y };
+            let start = offset + row * row_size;
+            for pixel in bytes[start..start + w as usize * pixel_size].chunks(pixel_size) {
+                let value = if pixel_size == 4 { read_u32(pixel, 0) } else { pixel[0] as u32 | (pixel[1] as u32) << 8 | (pixel[2] as u32) << 16 };
+                data.push(Color::rgba(
+                    channel(value, masks.0),
+                    channel(value, masks.1),
+                    channel(value, masks.2),
+                    if masks.3 == 0 { 255 } else { channel(value, masks.3) }
+                ));
+            }
+        }
+
+        // Many programs write 32-bit images with the alpha byte left as zero, so treat those as opaque
+        if masks.3 != 0 && data.iter().all(|color| color.a() == 0) {
+            for color in data.iter_mut() {
+                *color = Color::rgb(color.r(), color.g(), color.b());
+            }
+        }
+
+        Some(BmpFile::new(w, h, data))
+    }
+
+    /// Load a PNG file into a bitmap, so it can be drawn the same way
+    #[cfg(feature = "png")]
+    pub fn from_png(path: &str) -> Option<Self> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(_) => return None
+        };
+
+        // Expand palettes and small gray depths to 8 bits per channel
+        let mut decoder = png::Decoder::new(file);
+        decoder.set(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let (info, mut reader) = match decoder.read_info() {
+            Ok(result) => result,
+            Err(_) => return None
+        };
+
+        let mut buf = vec![0; info.buffer_size()];
+        if reader.next_frame(&mut buf).is_err() {
+            return None;
+        }
+
+        let channels = match info.color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::RGB => 3,
+            png::ColorType::RGBA => 4,
+            png::ColorType::Indexed => return None
+        };
+
+        let mut data = Vec::with_capacity(info.width as usize * info.height as usize);
+        for row in buf.chunks(info.line_size) {
+            for pixel in row[..info.width as usize * channels].chunks(channels) {
+                data.push(match channels {
+                    1 => Color::rgb(pixel[0], pixel[0], pixel[0]),
+                    2 => Color::rgba(pixel[0], pixel[0], pixel[0], pixel[1]),
+                    3 => Color::rgb(pixel[0], pixel[1], pixel[2]),
+                    _ => Color::rgba(pixel[0], pixel[1], pixel[2], pixel[3])
+                });
+            }
+        }
+
+        Some(BmpFile::new(info.width, info.height, data))
+    }
+
+    /// Get width
+    pub fn width(&self) -> u32 {
+        self.w
+    }
+
+    /// Get height
+    pub fn height(&self) -> u32 {
+        self.h
+    }
+
+    /// Access the pixel data
+    pub fn data(&self) -> &[Color] {
+        &self.data
+    }
+
+    /// Encode as a 32-bit BMP
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode(self.w, self.h, &self.data)
+    }
+
+    /// Save as a 32-bit BMP file
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        save(path, self.w, self.h, &self.data)
+    }
+}
+
+/// Extract the channel selected by `mask` from a pixel, scaled to 8 bits
+fn channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let max = (mask >> shift) as u64;
+    (((value & mask) >> shift) as u64 * 255 / max) as u8
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    bytes[offset] as u16 | (bytes[offset + 1] as u16) << 8
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    read_u16(bytes, offset) as u32 | (read_u16(bytes, offset + 2) as u32) << 16
+}
+
+/// Save pixel data as a 32-bit BMP file
+pub fn save(path: &str, w: u32, h: u32, data: &[Color]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(&encode(w, h, data))
+}
+
+/// Encode pixel data as a 32-bit BMP, with rows stored bottom-up as BGRA
+pub fn encode(w: u32, h: u32, data: &[Color]) -> Vec<u8> {
+    let pixels_size = w as usize * h as usize * 4;
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + pixels_size);
+
+    fn push_u16(bytes: &mut Vec<u8>, value: u16) {
+        bytes.push(value as u8);
+        bytes.push((value >> 8) as u8);
+    }
+
+    fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+        push_u16(bytes, value as u16);
+        push_u16(bytes, (value >> 16) as u16);
+    }
+
+    // File header
+    bytes.extend_from_slice(b"BM");
+    push_u32(&mut bytes, (HEADER_SIZE + pixels_size) as u32);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, HEADER_SIZE as u32);
+
+    // Info header
+    push_u32(&mut bytes, 40);
+    push_u32(&mut bytes, w);
+    push_u32(&mut bytes, h);
+    push_u16(&mut bytes, 1);
+    push_u16(&mut bytes, 32);
+    push_u32(&mut bytes, BI_RGB);
+    push_u32(&mut bytes, pixels_size as u32);
+    push_u32(&mut bytes, 2835);
+    push_u32(&mut bytes, 2835);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 0);
+
+    for y in (0..h as usize).rev() {
+        for x in 0..w as usize {
+            let color = data.get(y * w as usize + x).map_or(0, |color| color.data);
+            push_u32(&mut bytes, color);
+        }
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use color::Color;
+    use super::{BmpFile, BI_BITFIELDS};
+
+    fn channels(color: &Color) -> (u8, u8, u8, u8) {
+        (color.r(), color.g(), color.b(), color.a())
+    }
+
+    fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+        for i in 0..4 {
+            bytes.push((value >> (i * 8)) as u8);
+        }
+    }
+
+    #[test]
+    fn round_trip_keeps_alpha() {
+        let data = vec![
+            Color::rgba(255, 0, 0, 255), Color::rgba(0, 255, 0, 128),
+            Color::rgba(0, 0, 255, 0), Color::rgba(10, 20, 30, 64),
+        ];
+        let bmp = BmpFile::from_bytes(&BmpFile::new(2, 2, data).to_bytes()).unwrap();
+        assert_eq!((bmp.width(), bmp.height()), (2, 2));
+        let pixels: Vec<_> = bmp.data().iter().map(channels).collect();
+        assert_eq!(pixels, vec![(255, 0, 0, 255), (0, 255, 0, 128), (0, 0, 255, 0), (10, 20, 30, 64)]);
+    }
+
+    #[test]
+    fn zero_alpha_is_opaque() {
+        let data = vec![Color::rgba(1, 2, 3, 0), Color::rgba(4, 5, 6, 0)];
+        let bmp = BmpFile::from_bytes(&BmpFile::new(2, 1, data).to_bytes()).unwrap();
+        let pixels: Vec<_> = bmp.data().iter().map(channels).collect();
+        assert_eq!(pixels, vec![(1, 2, 3, 255), (4, 5, 6, 255)]);
+    }
+
+    #[test]
+    fn bitfields_with_alpha_mask() {
+        // A top-down 2x1 image with a V3 info header and bytes stored as RGBA
+        let mut bytes = b"BM".to_vec();
+        push_u32(&mut bytes, 14 + 56 + 8);
+        push_u32(&mut bytes, 0);
+        push_u32(&mut bytes, 14 + 56);
+        push_u32(&mut bytes, 56);
+        push_u32(&mut bytes, 2);
+        push_u32(&mut bytes, -1i32 as u32);
+        bytes.extend_from_slice(&[1, 0, 32, 0]);
+        push_u32(&mut bytes, BI_BITFIELDS);
+        for _ in 0..5 {
+            push_u32(&mut bytes, 0);
+        }
+        push_u32(&mut bytes, 0x000000FF);
+        push_u32(&mut bytes, 0x0000FF00);
+        push_u32(&mut bytes, 0x00FF0000);
+        push_u32(&mut bytes, 0xFF000000);
+        bytes.extend_from_slice(&[10, 20, 30, 40, 50, 60, 70, 80]);
+
+        let bmp = BmpFile::from_bytes(&bytes).unwrap();
+        assert_eq!((bmp.width(), bmp.height()), (2, 1));
+        let pixels: Vec<_> = bmp.data().iter().map(channels).collect();
+        assert_eq!(pixels, vec![(10, 20, 30, 40), (50, 60, 70, 80)]);
+    }
+
+    #[test]
+    fn rejects_truncated() {
+        let bytes = BmpFile::new(2, 2, vec![Color::rgb(0, 0, 0); 4]).to_bytes();
+        assert!(BmpFile::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        assert!(BmpFile::from_bytes(b"BM").is_none());
+    }
+}
@@ -0,0 +1,69 @@
+use color::Color;
+use renderer::Renderer;
+
+/// An in-memory buffer that can be drawn to and then displayed on a window
+pub struct Canvas {
+    /// The width of the canvas
+    w: u32,
+    /// The height of the canvas
+    h: u32,
+    /// The clipping rectangle
+    clip: Option<(i32, i32, u32, u32)>,
+    /// The canvas data
+    data: Vec<Color>,
+}
+
+impl Renderer for Canvas {
+    /// Get width
+    fn width(&self) -> u32 {
+        self.w
+    }
+
+    /// Get height
+    fn height(&self) -> u32 {
+        self.h
+    }
+
+    /// Access pixel buffer
+    fn data(&self) -> &[Color] {
+        &self.data
+    }
+
+    /// Access pixel buffer mutably
+    fn data_mut(&mut self) -> &mut [Color] {
+        &mut self.data
+    }
+
+    /// A canvas has nothing to flip
+    fn sync(&mut self) -> bool {
+        true
+    }
+
+    /// Get the clipping rectangle
+    fn clip(&self) -> Option<(i32, i32, u32, u32)> {
+        self.clip
+    }
+}
+
+impl Canvas {
+    /// Create a new, fully transparent canvas
+    pub fn new(w: u32, h: u32) -> Self {
+        Canvas::from_data(w, h, vec![Color::rgba(0, 0, 0, 0); w as usize * h as usize])
+    }
+
+    /// Create a canvas from existing pixel data, row by row from the top left. The data is resized to fit
+    pub fn from_data(w: u32, h: u32, mut data: Vec<Color>) -> Self {
+        data.resize(w as usize * h as usize, Color::rgba(0, 0, 0, 0));
+        Canvas {
+            w: w,
+            h: h,
+            clip: None,
+            data: data,
+        }
+    }
+
+    /// Set the clipping rectangle, or clear it with `None`
+    pub fn set_clip(&mut self, rect: Option<(i32, i32, u32, u32)>) {
+        self.clip = rect;
+    }
+}
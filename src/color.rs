@@ -1,4 +1,9 @@
-/// A color
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A color, packed as 0xAARRGGBB in `data`
 #[derive(Copy, Clone)]
 #[repr(packed)]
 pub struct Color {
@@ -16,6 +21,52 @@ impl Color {
         Color { data: ((a as u32) << 24) | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32) }
     }
 
+    /// Parse a color from a `#RRGGBB` or `#RRGGBBAA` hex string, the `#` being optional
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = if hex.starts_with('#') { &hex[1..] } else { hex };
+        if ! hex.chars().all(|c| c.is_digit(16)) {
+            return None;
+        }
+
+        match hex.len() {
+            6 => u32::from_str_radix(hex, 16).ok().map(|rgb| Color { data: 0xFF000000 | rgb }),
+            8 => u32::from_str_radix(hex, 16).ok().map(|rgba| Color { data: (rgba >> 8) | (rgba << 24) }),
+            _ => None
+        }
+    }
+
+    /// Create a new color from HSV, with hue in degrees and saturation and value from 0.0 to 1.0
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        let s = s.max(0.0).min(1.0);
+        let v = v.max(0.0).min(1.0);
+        let c = v * s;
+        Color::from_chroma(h, c, v - c)
+    }
+
+    /// Create a new color from HSL, with hue in degrees and saturation and lightness from 0.0 to 1.0
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let s = s.max(0.0).min(1.0);
+        let l = l.max(0.0).min(1.0);
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        Color::from_chroma(h, c, l - c / 2.0)
+    }
+
+    fn from_chroma(h: f32, c: f32, m: f32) -> Self {
+        let h = h % 360.0;
+        let h = (if h < 0.0 { h + 360.0 } else { h }) / 60.0;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let channel = |value: f32| ((value + m) * 255.0).round() as u8;
+        Color::rgb(channel(r), channel(g), channel(b))
+    }
+
     /// Get the r value
     pub fn r(&self) -> u8 {
         ((self.data & 0x00FF0000) >> 16) as u8
@@ -36,6 +87,21 @@ impl Color {
         ((self.data & 0xFF000000) >> 24) as u8
     }
 
+    /// Composite `over` on top of this color, using the source-over operator with straight alpha
+    pub fn blend(self, over: Color) -> Color {
+        let src_a = over.a() as u32;
+        let dst_a = self.a() as u32 * (255 - src_a) / 255;
+        let out_a = src_a + dst_a;
+        if out_a == 0 {
+            return Color::rgba(0, 0, 0, 0);
+        }
+
+        let channel = |src: u8, dst: u8| -> u8 {
+            ((src as u32 * src_a + dst as u32 * dst_a + out_a / 2) / out_a) as u8
+        };
+        Color::rgba(channel(over.r(), self.r()), channel(over.g(), self.g()), channel(over.b(), self.b()), out_a as u8)
+    }
+
     /// Interpolate between two colors
     pub fn interpolate(start_color: Color, end_color: Color, scale: f64) -> Color {
         let r = Color::interp(start_color.r(), end_color.r(), scale);
@@ -45,7 +111,98 @@ impl Color {
         Color::rgba(r, g, b, a)
     }
 
+    /// Linearly interpolate between two colors, with `t` clamped to 0.0 to 1.0
+    pub fn lerp(start_color: Color, end_color: Color, t: f32) -> Color {
+        Color::interpolate(start_color, end_color, t.max(0.0).min(1.0) as f64)
+    }
+
     fn interp(start_color: u8, end_color: u8, scale: f64) -> u8 {
         ((end_color as f64 - start_color as f64) * scale + start_color as f64) as u8
     }
 }
+
+impl From<u32> for Color {
+    /// Convert from a packed 0xAARRGGBB value
+    fn from(data: u32) -> Self {
+        Color { data: data }
+    }
+}
+
+impl From<Color> for u32 {
+    /// Convert to a packed 0xAARRGGBB value
+    fn from(color: Color) -> Self {
+        color.data
+    }
+}
+
+impl From<(u8, u8, u8)> for Color {
+    /// Convert from an opaque (r, g, b) tuple
+    fn from(rgb: (u8, u8, u8)) -> Self {
+        Color::rgb(rgb.0, rgb.1, rgb.2)
+    }
+}
+
+impl From<(u8, u8, u8, u8)> for Color {
+    /// Convert from an (r, g, b, a) tuple
+    fn from(rgba: (u8, u8, u8, u8)) -> Self {
+        Color::rgba(rgba.0, rgba.1, rgba.2, rgba.3)
+    }
+}
+
+impl fmt::Debug for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Color")
+            .field("r", &self.r())
+            .field("g", &self.g())
+            .field("b", &self.b())
+            .field("a", &self.a())
+            .finish()
+    }
+}
+
+impl fmt::Display for Color {
+    /// Format as `#RRGGBBAA`, which `Color::from_hex` accepts
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02X}{:02X}{:02X}{:02X}", self.r(), self.g(), self.b(), self.a())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Color {
+    /// Serialize as a `#RRGGBBAA` string
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Color {
+    /// Deserialize from a `#RRGGBB` or `#RRGGBBAA` string
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Color::from_hex(&hex).ok_or_else(|| de::Error::custom(format!("invalid color: {}", hex)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Color;
+
+    fn channels(color: Color) -> (u8, u8, u8, u8) {
+        (color.r(), color.g(), color.b(), color.a())
+    }
+
+    #[test]
+    fn blend_half_white_over_black() {
+        let color = Color::rgb(0, 0, 0).blend(Color::rgba(255, 255, 255, 128));
+        assert_eq!(channels(color), (128, 128, 128, 255));
+    }
+
+    #[test]
+    fn blend_extremes() {
+        let black = Color::rgb(0, 0, 0);
+        assert_eq!(channels(black.blend(Color::rgb(10, 20, 30))), (10, 20, 30, 255));
+        assert_eq!(channels(black.blend(Color::rgba(10, 20, 30, 0))), (0, 0, 0, 255));
+        assert_eq!(channels(Color::rgba(0, 0, 0, 0).blend(Color::rgba(0, 0, 0, 0))), (0, 0, 0, 0));
+    }
+}
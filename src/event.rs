@@ -8,22 +8,53 @@ pub const EVENT_QUIT: i64 = 3;
 pub const EVENT_FOCUS: i64 = 4;
 pub const EVENT_MOVE: i64 = 5;
 pub const EVENT_RESIZE: i64 = 6;
+pub const EVENT_SCROLL: i64 = 7;
+pub const EVENT_BUTTON: i64 = 8;
+pub const EVENT_TEXT_INPUT: i64 = 9;
+pub const EVENT_CONTROLLER: i64 = 10;
+pub const EVENT_CONTROLLER_AXIS: i64 = 11;
+pub const EVENT_CONTROLLER_DEVICE: i64 = 12;
+pub const EVENT_DROP: i64 = 13;
+pub const EVENT_MOUSE_DOUBLE_CLICK: i64 = 14;
+pub const EVENT_HOVER: i64 = 15;
+pub const EVENT_MOUSE_RELATIVE: i64 = 16;
 
 /// An optional event
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EventOption {
     /// A mouse event
     Mouse(MouseEvent),
+    /// A relative mouse motion event
+    MouseRelative(MouseRelativeEvent),
     /// A key event
     Key(KeyEvent),
     /// A quit request event
     Quit(QuitEvent),
     /// A focus event
     Focus(FocusEvent),
+    /// A hover event
+    Hover(HoverEvent),
     /// A move event
     Move(MoveEvent),
     /// A resize event
     Resize(ResizeEvent),
+    /// A scroll event
+    Scroll(ScrollEvent),
+    /// A mouse button event
+    Button(ButtonEvent),
+    /// A mouse double click event
+    MouseDoubleClick(MouseDoubleClickEvent),
+    /// A text input event
+    TextInput(TextInputEvent),
+    /// A game controller button event
+    Controller(ControllerEvent),
+    /// A game controller axis event
+    ControllerAxis(ControllerAxisEvent),
+    /// A game controller connection event
+    ControllerDevice(ControllerDeviceEvent),
+    /// A file drop event
+    Drop(DropEvent),
     /// An unknown event
     Unknown(Event),
     /// No event
@@ -32,6 +63,7 @@ pub enum EventOption {
 
 /// An event
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(packed)]
 pub struct Event {
     pub code: i64,
@@ -57,11 +89,21 @@ impl Event {
         match self.code {
             EVENT_NONE => EventOption::None,
             EVENT_MOUSE => EventOption::Mouse(MouseEvent::from_event(self)),
+            EVENT_MOUSE_RELATIVE => EventOption::MouseRelative(MouseRelativeEvent::from_event(self)),
             EVENT_KEY => EventOption::Key(KeyEvent::from_event(self)),
             EVENT_QUIT => EventOption::Quit(QuitEvent::from_event(self)),
             EVENT_FOCUS => EventOption::Focus(FocusEvent::from_event(self)),
+            EVENT_HOVER => EventOption::Hover(HoverEvent::from_event(self)),
             EVENT_MOVE => EventOption::Move(MoveEvent::from_event(self)),
             EVENT_RESIZE => EventOption::Resize(ResizeEvent::from_event(self)),
+            EVENT_SCROLL => EventOption::Scroll(ScrollEvent::from_event(self)),
+            EVENT_BUTTON => EventOption::Button(ButtonEvent::from_event(self)),
+            EVENT_MOUSE_DOUBLE_CLICK => EventOption::MouseDoubleClick(MouseDoubleClickEvent::from_event(self)),
+            EVENT_TEXT_INPUT => EventOption::TextInput(TextInputEvent::from_event(self)),
+            EVENT_CONTROLLER => EventOption::Controller(ControllerEvent::from_event(self)),
+            EVENT_CONTROLLER_AXIS => EventOption::ControllerAxis(ControllerAxisEvent::from_event(self)),
+            EVENT_CONTROLLER_DEVICE => EventOption::ControllerDevice(ControllerDeviceEvent::from_event(self)),
+            EVENT_DROP => EventOption::Drop(DropEvent::from_event(self)),
             _ => EventOption::Unknown(self),
         }
     }
@@ -86,6 +128,7 @@ impl DerefMut for Event {
 
 /// A event related to the mouse
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MouseEvent {
     /// The x coordinate of the mouse
     pub x: i32,
@@ -123,6 +166,106 @@ impl MouseEvent {
     }
 }
 
+/// A relative mouse motion event, sent with each mouse event that moves the mouse. It keeps reporting motion in relative mouse mode, where the position stops changing
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MouseRelativeEvent {
+    /// The horizontal motion, positive to the right
+    pub dx: i32,
+    /// The vertical motion, positive downwards
+    pub dy: i32,
+}
+
+impl MouseRelativeEvent {
+    /// Convert to an `Event`
+    pub fn to_event(&self) -> Event {
+        Event {
+            code: EVENT_MOUSE_RELATIVE,
+            a: self.dx as i64,
+            b: self.dy as i64,
+            c: 0,
+        }
+    }
+
+    /// Convert from an `Event`
+    pub fn from_event(event: Event) -> MouseRelativeEvent {
+        MouseRelativeEvent {
+            dx: event.a as i32,
+            dy: event.b as i32,
+        }
+    }
+}
+
+/// A mouse button event, sent when a button is pressed or released
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ButtonEvent {
+    /// Is the left button pressed?
+    pub left: bool,
+    /// Is the middle button pressed?
+    pub middle: bool,
+    /// Is the right button pressed?
+    pub right: bool,
+}
+
+impl ButtonEvent {
+    /// Convert to an `Event`
+    pub fn to_event(&self) -> Event {
+        Event {
+            code: EVENT_BUTTON,
+            a: self.left as i64 | (self.middle as i64) << 1 | (self.right as i64) << 2,
+            b: 0,
+            c: 0,
+        }
+    }
+
+    /// Convert an `Event` to a `ButtonEvent`
+    pub fn from_event(event: Event) -> ButtonEvent {
+        ButtonEvent {
+            left: event.a & 1 == 1,
+            middle: event.a & 2 == 2,
+            right: event.a & 4 == 4,
+        }
+    }
+}
+
+pub const BUTTON_LEFT: u8 = 1;
+pub const BUTTON_MIDDLE: u8 = 2;
+pub const BUTTON_RIGHT: u8 = 4;
+
+/// A mouse double click event, sent after the second of two quick presses of the same button in nearly the same place
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MouseDoubleClickEvent {
+    /// The x coordinate of the mouse
+    pub x: i32,
+    /// The y coordinate of the mouse
+    pub y: i32,
+    /// The button, one of the `BUTTON_*` constants
+    pub button: u8,
+}
+
+impl MouseDoubleClickEvent {
+    /// Convert to an `Event`
+    pub fn to_event(&self) -> Event {
+        Event {
+            code: EVENT_MOUSE_DOUBLE_CLICK,
+            a: self.x as i64,
+            b: self.y as i64,
+            c: self.button as i64,
+        }
+    }
+
+    /// Convert from an `Event`
+    pub fn from_event(event: Event) -> MouseDoubleClickEvent {
+        MouseDoubleClickEvent {
+            x: event.a as i32,
+            y: event.b as i32,
+            button: event.c as u8,
+        }
+    }
+}
+
 pub const K_A: u8 = 0x1E;
 pub const K_B: u8 = 0x30;
 pub const K_C: u8 = 0x2E;
@@ -244,16 +387,53 @@ pub const K_DEL: u8 = 0x53;
 pub const K_F11: u8 = 0x57;
 /// F12 key
 pub const K_F12: u8 = 0x58;
+/// Numpad 0 key, shared with insert
+pub const K_NUM_0: u8 = 0x52;
+/// Numpad 1 key, shared with end
+pub const K_NUM_1: u8 = 0x4F;
+/// Numpad 2 key, shared with down
+pub const K_NUM_2: u8 = 0x50;
+/// Numpad 3 key, shared with page down
+pub const K_NUM_3: u8 = 0x51;
+/// Numpad 4 key, shared with left
+pub const K_NUM_4: u8 = 0x4B;
+/// Numpad 5 key
+pub const K_NUM_5: u8 = 0x4C;
+/// Numpad 6 key, shared with right
+pub const K_NUM_6: u8 = 0x4D;
+/// Numpad 7 key, shared with home
+pub const K_NUM_7: u8 = 0x47;
+/// Numpad 8 key, shared with up
+pub const K_NUM_8: u8 = 0x48;
+/// Numpad 9 key, shared with page up
+pub const K_NUM_9: u8 = 0x49;
+/// Numpad period key, shared with delete
+pub const K_NUM_PERIOD: u8 = 0x53;
+/// Numpad plus key
+pub const K_NUM_PLUS: u8 = 0x4E;
+/// Numpad minus key
+pub const K_NUM_MINUS: u8 = 0x4A;
+/// Numpad asterisk key
+pub const K_NUM_ASTERISK: u8 = 0x37;
 
 /// A key event (such as a pressed key)
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KeyEvent {
-    /// The charecter of the key
+    /// The charecter of the key, which is ASCII only. Use `TextInputEvent` for other text
     pub character: char,
     /// The scancode of the key
     pub scancode: u8,
     /// Was it pressed?
     pub pressed: bool,
+    /// Was shift held?
+    pub shift: bool,
+    /// Was control held?
+    pub ctrl: bool,
+    /// Was alt held?
+    pub alt: bool,
+    /// The scancode of the key as given by the backend, or 0 if there is none. On SDL this is an `SDL_Scancode`, and keys without a `K_*` scancode are still sent with `scancode` and `character` set to 0
+    pub raw_scancode: i32,
 }
 
 impl KeyEvent {
@@ -262,8 +442,9 @@ impl KeyEvent {
         Event {
             code: EVENT_KEY,
             a: self.character as i64,
-            b: self.scancode as i64,
-            c: self.pressed as i64,
+            b: self.scancode as i64 | (self.raw_scancode as i64) << 32,
+            c: self.pressed as i64 | (self.shift as i64) << 1 | (self.ctrl as i64) << 2 |
+               (self.alt as i64) << 3,
         }
     }
 
@@ -272,12 +453,44 @@ impl KeyEvent {
         KeyEvent {
             character: char::from_u32(event.a as u32).unwrap_or('\0'),
             scancode: event.b as u8,
-            pressed: event.c > 0,
+            pressed: event.c & 1 == 1,
+            shift: event.c & 2 == 2,
+            ctrl: event.c & 4 == 4,
+            alt: event.c & 8 == 8,
+            raw_scancode: (event.b >> 32) as i32,
         }
     }
 }
 
+/// A text input event, sent once for each character of entered text
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextInputEvent {
+    /// The entered character
+    pub character: char,
+}
+
+impl TextInputEvent {
+    /// Convert to an `Event`
+    pub fn to_event(&self) -> Event {
+        Event {
+            code: EVENT_TEXT_INPUT,
+            a: self.character as i64,
+            b: 0,
+            c: 0,
+        }
+    }
+
+    /// Convert from an `Event`
+    pub fn from_event(event: Event) -> TextInputEvent {
+        TextInputEvent {
+            character: char::from_u32(event.a as u32).unwrap_or('\0'),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct QuitEvent;
 
 impl QuitEvent {
@@ -297,6 +510,7 @@ impl QuitEvent {
 
 /// A focus event
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FocusEvent {
     /// True if window has been focused, false if not
     pub focused: bool
@@ -319,8 +533,34 @@ impl FocusEvent {
     }
 }
 
+/// A hover event, sent when the mouse enters or leaves the window
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HoverEvent {
+    /// True if the mouse has entered the window, false if it has left
+    pub entered: bool
+}
+
+impl HoverEvent {
+    pub fn to_event(&self) -> Event {
+        Event {
+            code: EVENT_HOVER,
+            a: self.entered as i64,
+            b: 0,
+            c: 0,
+        }
+    }
+
+    pub fn from_event(event: Event) -> HoverEvent {
+        HoverEvent {
+            entered: event.a > 0
+        }
+    }
+}
+
 /// A move event
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MoveEvent {
     pub x: i32,
     pub y: i32
@@ -346,6 +586,7 @@ impl MoveEvent {
 
 /// A resize event
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ResizeEvent {
     pub width: u32,
     pub height: u32
@@ -368,3 +609,172 @@ impl ResizeEvent {
         }
     }
 }
+
+/// A mouse scroll event
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScrollEvent {
+    /// The horizontal scroll amount, positive to the right
+    pub x: i32,
+    /// The vertical scroll amount, positive away from the user
+    pub y: i32
+}
+
+impl ScrollEvent {
+    pub fn to_event(&self) -> Event {
+        Event {
+            code: EVENT_SCROLL,
+            a: self.x as i64,
+            b: self.y as i64,
+            c: 0,
+        }
+    }
+
+    pub fn from_event(event: Event) -> ScrollEvent {
+        ScrollEvent {
+            x: event.a as i32,
+            y: event.b as i32
+        }
+    }
+}
+
+pub const CONTROLLER_BUTTON_A: u8 = 0;
+pub const CONTROLLER_BUTTON_B: u8 = 1;
+pub const CONTROLLER_BUTTON_X: u8 = 2;
+pub const CONTROLLER_BUTTON_Y: u8 = 3;
+pub const CONTROLLER_BUTTON_BACK: u8 = 4;
+pub const CONTROLLER_BUTTON_GUIDE: u8 = 5;
+pub const CONTROLLER_BUTTON_START: u8 = 6;
+pub const CONTROLLER_BUTTON_LEFT_STICK: u8 = 7;
+pub const CONTROLLER_BUTTON_RIGHT_STICK: u8 = 8;
+pub const CONTROLLER_BUTTON_LEFT_SHOULDER: u8 = 9;
+pub const CONTROLLER_BUTTON_RIGHT_SHOULDER: u8 = 10;
+pub const CONTROLLER_BUTTON_UP: u8 = 11;
+pub const CONTROLLER_BUTTON_DOWN: u8 = 12;
+pub const CONTROLLER_BUTTON_LEFT: u8 = 13;
+pub const CONTROLLER_BUTTON_RIGHT: u8 = 14;
+
+/// A game controller button event
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ControllerEvent {
+    /// The controller, matching the id of its `ControllerDeviceEvent`
+    pub id: i32,
+    /// The button, one of the `CONTROLLER_BUTTON_*` constants
+    pub button: u8,
+    /// Was it pressed?
+    pub pressed: bool,
+}
+
+impl ControllerEvent {
+    /// Convert to an `Event`
+    pub fn to_event(&self) -> Event {
+        Event {
+            code: EVENT_CONTROLLER,
+            a: self.id as i64,
+            b: self.button as i64,
+            c: self.pressed as i64,
+        }
+    }
+
+    /// Convert from an `Event`
+    pub fn from_event(event: Event) -> ControllerEvent {
+        ControllerEvent {
+            id: event.a as i32,
+            button: event.b as u8,
+            pressed: event.c > 0,
+        }
+    }
+}
+
+pub const CONTROLLER_AXIS_LEFT_X: u8 = 0;
+pub const CONTROLLER_AXIS_LEFT_Y: u8 = 1;
+pub const CONTROLLER_AXIS_RIGHT_X: u8 = 2;
+pub const CONTROLLER_AXIS_RIGHT_Y: u8 = 3;
+pub const CONTROLLER_AXIS_TRIGGER_LEFT: u8 = 4;
+pub const CONTROLLER_AXIS_TRIGGER_RIGHT: u8 = 5;
+
+/// A game controller axis event
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ControllerAxisEvent {
+    /// The controller, matching the id of its `ControllerDeviceEvent`
+    pub id: i32,
+    /// The axis, one of the `CONTROLLER_AXIS_*` constants
+    pub axis: u8,
+    /// The position of the axis. Sticks range from -32768 to 32767 and triggers from 0 to 32767
+    pub value: i16,
+}
+
+impl ControllerAxisEvent {
+    /// Convert to an `Event`
+    pub fn to_event(&self) -> Event {
+        Event {
+            code: EVENT_CONTROLLER_AXIS,
+            a: self.id as i64,
+            b: self.axis as i64,
+            c: self.value as i64,
+        }
+    }
+
+    /// Convert from an `Event`
+    pub fn from_event(event: Event) -> ControllerAxisEvent {
+        ControllerAxisEvent {
+            id: event.a as i32,
+            axis: event.b as u8,
+            value: event.c as i16,
+        }
+    }
+}
+
+/// A game controller connection event, sent when a controller is plugged in or removed
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ControllerDeviceEvent {
+    /// The controller
+    pub id: i32,
+    /// True if the controller was connected, false if it was removed
+    pub connected: bool,
+}
+
+impl ControllerDeviceEvent {
+    /// Convert to an `Event`
+    pub fn to_event(&self) -> Event {
+        Event {
+            code: EVENT_CONTROLLER_DEVICE,
+            a: self.id as i64,
+            b: self.connected as i64,
+            c: 0,
+        }
+    }
+
+    /// Convert from an `Event`
+    pub fn from_event(event: Event) -> ControllerDeviceEvent {
+        ControllerDeviceEvent {
+            id: event.a as i32,
+            connected: event.b > 0,
+        }
+    }
+}
+
+/// A file drop event, sent once for each file dropped onto the window. Events cannot hold the path, so get it with `Window::pop_drop_path`
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DropEvent;
+
+impl DropEvent {
+    /// Convert to an `Event`
+    pub fn to_event(&self) -> Event {
+        Event {
+            code: EVENT_DROP,
+            a: 0,
+            b: 0,
+            c: 0,
+        }
+    }
+
+    /// Convert from an `Event`
+    pub fn from_event(_: Event) -> DropEvent {
+        DropEvent
+    }
+}
@@ -6,15 +6,24 @@
 #![deny(warnings)]
 
 extern crate core;
+#[cfg(feature = "png")]
+extern crate png;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
 
 pub static FONT: &'static [u8] = include_bytes!("../res/unifont.font");
 
+pub use bmp::BmpFile;
+pub use canvas::Canvas;
 pub use color::Color;
 pub use event::*;
 pub use imp::*;
-pub use renderer::Renderer;
+pub use renderer::{Adjust, Renderer};
 pub use graphicspath::GraphicsPath;
 
+pub mod bmp;
+pub mod canvas;
 pub mod color;
 pub mod event;
 pub mod graphicspath;
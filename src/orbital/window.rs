@@ -2,13 +2,74 @@ use std::{mem, slice, thread};
 use std::fs::File;
 use std::io::{Read, Write};
 use std::os::unix::io::{AsRawFd, RawFd};
+use std::time::{Duration, Instant};
 
-use super::syscall;
+use super::{get_display_size, syscall};
 
+use FONT;
+use bmp::BmpFile;
 use color::Color;
-use event::Event;
+use event::{ButtonEvent, Event, KeyEvent, MouseEvent, MouseDoubleClickEvent, MouseRelativeEvent, BUTTON_LEFT, BUTTON_MIDDLE, BUTTON_RIGHT, EVENT_KEY, EVENT_MOUSE, EVENT_MOVE, EVENT_QUIT};
 use renderer::Renderer;
 
+/// How far in pixels the mouse can move between the presses of a double click
+const DOUBLE_CLICK_DISTANCE: i32 = 4;
+
+/// A window creation flag
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WindowFlag {
+    /// Do not wait for events
+    Async,
+    /// Do not draw a border or title bar
+    Borderless,
+    /// Allow the user to resize the window
+    Resizable,
+    /// Keep the window above other windows
+    Front,
+    /// Present with a hardware accelerated renderer. Orbital always composites windows itself, so this has no effect
+    Accelerated,
+    /// Wait for the display to refresh when presenting. Orbital presents windows itself, so this has no effect
+    Vsync,
+}
+
+/// A fullscreen mode
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FullscreenMode {
+    /// A normal window
+    Off,
+    /// A borderless window covering the desktop, keeping the display mode
+    Desktop,
+    /// Exclusive fullscreen, changing the display mode to the window size
+    Exclusive,
+}
+
+/// A system mouse cursor shape
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CursorStyle {
+    /// The default arrow
+    Arrow,
+    /// A pointing hand, for links and buttons
+    Hand,
+    /// A text cursor
+    IBeam,
+    /// A crosshair
+    Crosshair,
+    /// A busy indicator
+    Wait,
+    /// A double arrow pointing northwest and southeast
+    SizeNWSE,
+    /// A double arrow pointing northeast and southwest
+    SizeNESW,
+    /// A double arrow pointing west and east
+    SizeWE,
+    /// A double arrow pointing north and south
+    SizeNS,
+    /// A four pointed arrow
+    SizeAll,
+    /// A slashed circle, for actions that are not allowed
+    No,
+}
+
 /// A window
 pub struct Window {
     /// The x coordinate of the window
@@ -23,6 +84,28 @@ pub struct Window {
     t: String,
     /// True if the window should not wait for events
     async: bool,
+    /// True if the window has been asked to close
+    closing: bool,
+    /// The flags the window was opened with, in the form of the orbital path
+    flags: String,
+    /// The time, position and button of the last press that could start a double click
+    last_click: Option<(Instant, i32, i32, u8)>,
+    /// The longest time between the presses of a double click
+    double_click_ms: u32,
+    /// The shortest time between presents, if the frame rate is limited
+    frame_interval: Option<Duration>,
+    /// The time of the last present
+    last_frame: Option<Instant>,
+    /// The clipping rectangle
+    clip: Option<(i32, i32, u32, u32)>,
+    /// The custom font and its glyph height, if one is set
+    font: Option<(Vec<u8>, usize)>,
+    /// The last mouse state received
+    mouse: MouseEvent,
+    /// The keys currently held down, by scancode
+    keys: [bool; 256],
+    /// The position and size to restore when leaving fullscreen
+    restore: Option<(i32, i32, u32, u32)>,
     /// The input scheme
     file: File,
     /// Window data
@@ -52,19 +135,46 @@ impl Renderer for Window {
 
     /// Flip the buffer
     fn sync(&mut self) -> bool {
+        self.wait_frame();
         self.file.sync_data().is_ok()
     }
+
+    /// Get the clipping rectangle
+    fn clip(&self) -> Option<(i32, i32, u32, u32)> {
+        self.clip
+    }
+
+    /// Get the custom font if one is set, or the built-in font
+    fn font(&self) -> (&[u8], usize) {
+        match self.font {
+            Some((ref data, height)) => (data, height),
+            None => (FONT, 16)
+        }
+    }
 }
 
 impl Window {
     /// Create a new window
     pub fn new(x: i32, y: i32, w: u32, h: u32, title: &str) -> Option<Self> {
-        Window::new_flags(x, y, w, h, title, false)
+        Window::new_flags(x, y, w, h, title, &[])
     }
 
     /// Create a new window with flags
-    pub fn new_flags(x: i32, y: i32, w: u32, h: u32, title: &str, async: bool) -> Option<Self> {
-        if let Ok(file) = File::open(&format!("orbital:{}/{}/{}/{}/{}/{}", if async { "a" } else { "" }, x, y, w, h, title)) {
+    pub fn new_flags(x: i32, y: i32, w: u32, h: u32, title: &str, flags: &[WindowFlag]) -> Option<Self> {
+        let async = flags.contains(&WindowFlag::Async);
+
+        let mut flag_str = String::new();
+        for flag in flags.iter() {
+            match *flag {
+                WindowFlag::Async => flag_str.push('a'),
+                WindowFlag::Borderless => flag_str.push('l'),
+                WindowFlag::Resizable => flag_str.push('r'),
+                WindowFlag::Front => flag_str.push('f'),
+                WindowFlag::Accelerated | WindowFlag::Vsync => ()
+            }
+        }
+
+        if let Ok(file) = File::open(&format!("orbital:{}/{}/{}/{}/{}/{}", flag_str, x, y, w, h, title)) {
             if let Ok(address) = unsafe { syscall::fmap(file.as_raw_fd(), 0, (w * h * 4) as usize) } {
                 Some(Window {
                     x: x,
@@ -73,6 +183,23 @@ impl Window {
                     h: h,
                     t: title.to_string(),
                     async: async,
+                    closing: false,
+                    flags: flag_str,
+                    last_click: None,
+                    double_click_ms: 500,
+                    frame_interval: None,
+                    last_frame: None,
+                    clip: None,
+                    font: None,
+                    mouse: MouseEvent {
+                        x: 0,
+                        y: 0,
+                        left_button: false,
+                        middle_button: false,
+                        right_button: false,
+                    },
+                    keys: [false; 256],
+                    restore: None,
                     file: file,
                     data: unsafe { slice::from_raw_parts_mut(address as *mut Color, (w * h) as usize) },
                 })
@@ -84,36 +211,35 @@ impl Window {
         }
     }
 
-    // TODO: Replace with smarter mechanism, maybe a move event?
+    /// Create a new window that renders into memory only. Orbital windows are always backed by the display server, so this is not supported
+    pub fn new_offscreen(_w: u32, _h: u32) -> Option<Self> {
+        None
+    }
+
+    /// Read the position, size and title back from the display server. Move events already keep the position current, so this is only needed after changing the window from the program
     pub fn sync_path(&mut self) {
         let mut buf: [u8; 4096] = [0; 4096];
         if let Ok(count) = syscall::fpath(self.file.as_raw_fd() as usize, &mut buf) {
-            let path = unsafe { String::from_utf8_unchecked(Vec::from(&buf[..count])) };
-            // orbital:/x/y/w/h/t
-            let mut parts = path.split('/').skip(1);
-            if let Some(x) = parts.next() {
-                self.x = x.parse::<i32>().unwrap_or(0);
-            }
-            if let Some(y) = parts.next() {
-                self.y = y.parse::<i32>().unwrap_or(0);
-            }
-            if let Some(w) = parts.next() {
-                self.w = w.parse::<u32>().unwrap_or(0);
-            }
-            if let Some(h) = parts.next() {
-                self.h = h.parse::<u32>().unwrap_or(0);
-            }
+            let (x, y, w, h, title) = parse_path(&buf[..count]);
+            self.x = x;
+            self.y = y;
+            self.w = w;
+            self.h = h;
+            self.t = title;
         }
     }
 
+    /// Flip only a region of the window buffer. Orbital always flips the whole buffer
+    pub fn sync_rect(&mut self, _x: i32, _y: i32, _w: u32, _h: u32) -> bool {
+        self.sync()
+    }
+
     /// Get x
-    // TODO: Sync with window movements
     pub fn x(&self) -> i32 {
         self.x
     }
 
     /// Get y
-    // TODO: Sync with window movements
     pub fn y(&self) -> i32 {
         self.y
     }
@@ -123,13 +249,23 @@ impl Window {
         self.t.clone()
     }
 
-    // Set position
+    /// Get the scale of the display the window is on, relative to a standard 96 DPI display. Orbital does not report DPI, so this is always 1.0
+    pub fn scale(&self) -> f32 {
+        1.0
+    }
+
+    /// Get the position and size of the display the window is on
+    pub fn display_bounds(&self) -> Option<(i32, i32, u32, u32)> {
+        get_display_size().ok().map(|(width, height)| (0, 0, width, height))
+    }
+
+    /// Set position
     pub fn set_pos(&mut self, x: i32, y: i32) {
         let _ = self.file.write(&format!("P,{},{}", x, y).as_bytes());
         self.sync_path();
     }
 
-    // Set size
+    /// Set size
     pub fn set_size(&mut self, width: u32, height: u32) {
         //TODO: Improve safety and reliability
         unsafe {
@@ -143,33 +279,296 @@ impl Window {
         }
     }
 
+    /// Get the last known mouse position relative to the window and whether the left, middle and right buttons are pressed
+    pub fn mouse(&self) -> (i32, i32, bool, bool, bool) {
+        (self.mouse.x, self.mouse.y, self.mouse.left_button, self.mouse.middle_button, self.mouse.right_button)
+    }
+
+    /// Check if a key is currently held down, as tracked from received key events
+    pub fn key_pressed(&self, scancode: u8) -> bool {
+        self.keys[scancode as usize]
+    }
+
+    /// Pass the pixel buffer to `f` along with the row stride in pixels
+    pub fn with_pixels<F: FnMut(&mut [Color], usize)>(&mut self, mut f: F) {
+        let stride = self.w as usize;
+        f(&mut self.data, stride);
+    }
+
+    /// Take the path of the oldest dropped file. Orbital does not support dropping files, so there is never one
+    pub fn pop_drop_path(&mut self) -> Option<String> {
+        None
+    }
+
+    /// Set how many milliseconds apart two presses can be to count as a double click
+    pub fn set_double_click_ms(&mut self, ms: u32) {
+        self.double_click_ms = ms;
+    }
+
+    /// Limit how many times per second `sync` presents, sleeping for the rest of each frame. Passing 0 removes the limit
+    pub fn set_frame_rate(&mut self, fps: u32) {
+        self.frame_interval = if fps > 0 {
+            Some(Duration::new(0, 1_000_000_000 / fps))
+        } else {
+            None
+        };
+    }
+
+    /// Sleep until the current frame has lasted the frame interval, then start the next one
+    fn wait_frame(&mut self) {
+        if let (Some(interval), Some(last)) = (self.frame_interval, self.last_frame) {
+            let elapsed = last.elapsed();
+            if elapsed < interval {
+                thread::sleep(interval - elapsed);
+            }
+        }
+        self.last_frame = Some(Instant::now());
+    }
+
+    /// Record a button press, returning a double click event if it completes one
+    fn double_click(&mut self, x: i32, y: i32, button: u8) -> Option<Event> {
+        let now = Instant::now();
+        if let Some((time, last_x, last_y, last_button)) = self.last_click.take() {
+            let quick = now.duration_since(time) <= Duration::from_millis(self.double_click_ms as u64);
+            let near = (x - last_x).abs() <= DOUBLE_CLICK_DISTANCE && (y - last_y).abs() <= DOUBLE_CLICK_DISTANCE;
+            if button == last_button && quick && near {
+                return Some(MouseDoubleClickEvent {
+                    x: x,
+                    y: y,
+                    button: button
+                }.to_event());
+            }
+        }
+        self.last_click = Some((now, x, y, button));
+        None
+    }
+
+    /// Show or hide the mouse cursor
+    pub fn set_mouse_cursor(&mut self, visible: bool) {
+        let _ = self.file.write(&format!("M,C,{}", if visible { 1 } else { 0 }).as_bytes());
+    }
+
+    /// Set the shape of the mouse cursor while it is over the window. Orbital only has one cursor, so this does nothing
+    pub fn set_cursor(&mut self, _style: CursorStyle) {
+    }
+
+    /// Move the mouse cursor to a position relative to the window. Orbital does not let clients move the cursor, so this does nothing
+    pub fn set_mouse_pos(&mut self, _x: i32, _y: i32) {
+    }
+
+    /// Enable or disable relative mouse mode, which hides the cursor and reports motion without limits
+    pub fn set_mouse_relative(&mut self, relative: bool) {
+        let _ = self.file.write(&format!("M,R,{}", if relative { 1 } else { 0 }).as_bytes());
+    }
+
+    /// Set the fullscreen mode. Orbital has no display modes, so both fullscreen modes cover the display
+    pub fn set_fullscreen(&mut self, mode: FullscreenMode) {
+        if mode == FullscreenMode::Off {
+            if let Some((x, y, w, h)) = self.restore.take() {
+                self.set_size(w, h);
+                self.set_pos(x, y);
+            }
+        } else if let Ok((width, height)) = get_display_size() {
+            if self.restore.is_none() {
+                self.restore = Some((self.x, self.y, self.w, self.h));
+            }
+            self.set_pos(0, 0);
+            self.set_size(width, height);
+        }
+    }
+
+    /// Set the opacity of the whole window, from 0.0 for invisible to 1.0 for opaque. Orbital does not support this, so it returns false
+    pub fn set_opacity(&mut self, _opacity: f32) -> bool {
+        false
+    }
+
+    /// Minimize the window. Orbital cannot minimize windows, so this does nothing
+    pub fn minimize(&mut self) {
+    }
+
+    /// Maximize the window, covering the display
+    pub fn maximize(&mut self) {
+        self.set_fullscreen(FullscreenMode::Desktop);
+    }
+
+    /// Restore the window from being maximized
+    pub fn restore(&mut self) {
+        self.set_fullscreen(FullscreenMode::Off);
+    }
+
+    /// Keep the window above other windows, or stop doing so. Orbital only takes this when opening a window, so the window is reopened with the same contents. Returns false if that fails
+    pub fn set_always_on_top(&mut self, on: bool) -> bool {
+        if self.flags.contains('f') == on {
+            return true;
+        }
+
+        let flags = if on {
+            format!("{}f", self.flags)
+        } else {
+            self.flags.chars().filter(|&c| c != 'f').collect()
+        };
+
+        let file = match File::open(&format!("orbital:{}/{}/{}/{}/{}/{}", flags, self.x, self.y, self.w, self.h, self.t)) {
+            Ok(file) => file,
+            Err(_) => return false
+        };
+        let data = match unsafe { syscall::fmap(file.as_raw_fd(), 0, (self.w * self.h * 4) as usize) } {
+            Ok(address) => unsafe { slice::from_raw_parts_mut(address as *mut Color, (self.w * self.h) as usize) },
+            Err(_) => return false
+        };
+        data.copy_from_slice(self.data);
+
+        // Replacing the file closes the old window
+        let _ = unsafe { syscall::funmap(self.data.as_ptr() as usize) };
+        self.data = data;
+        self.file = file;
+        self.flags = flags;
+        self.file.sync_data().is_ok()
+    }
+
+    /// Turn vsync on or off. Orbital presents windows itself, so vsync is never on and this returns false when asked to turn it on
+    pub fn set_vsync(&mut self, on: bool) -> bool {
+        !on
+    }
+
+    /// Set the window icon from pixel data. Orbital does not show window icons, so this does nothing
+    pub fn set_icon(&mut self, _w: u32, _h: u32, _data: &[Color]) {
+    }
+
+    /// Set the window icon from a bitmap. Orbital does not show window icons, so this does nothing
+    pub fn set_icon_bmp(&mut self, _bmp: &BmpFile) {
+    }
+
+    /// Set the clipping rectangle, or clear it with `None`
+    pub fn set_clip(&mut self, rect: Option<(i32, i32, u32, u32)>) {
+        self.clip = rect;
+    }
+
+    /// Draw text with a custom bitmap font, which holds a glyph of `glyph_height` rows for each character, each row being one byte for 8 pixels. An empty font or a height of 0 goes back to the built-in font
+    pub fn set_font(&mut self, font: &[u8], glyph_height: usize) {
+        self.font = if font.is_empty() || glyph_height == 0 {
+            None
+        } else {
+            Some((font.to_vec(), glyph_height))
+        };
+    }
+
     /// Set title
     pub fn set_title(&mut self, title: &str) {
         let _ = self.file.write(&format!("T,{}", title).as_bytes());
         self.sync_path();
     }
 
+    /// Check if the window is still open, which stops being true once a quit event arrives or `close` is called
+    pub fn is_open(&self) -> bool {
+        ! self.closing
+    }
+
+    /// Request that the window close
+    pub fn close(&mut self) {
+        self.closing = true;
+    }
+
+    /// Call `f` for every event until it returns false or the window closes. A quit event is still passed to `f`
+    pub fn run<F: FnMut(&mut Window, Event) -> bool>(&mut self, mut f: F) {
+        loop {
+            let iter = self.events();
+            // A quit event marks the window closing as soon as it is read, so only stop early for one that `f` has seen
+            let quit_read = iter.events.iter().any(|event| event.code == EVENT_QUIT);
+            for event in iter {
+                let quit = event.code == EVENT_QUIT;
+                if ! f(self, event) || quit || (! quit_read && ! self.is_open()) {
+                    return;
+                }
+            }
+        }
+    }
+
     /// Blocking iterator over events
     pub fn events(&mut self) -> EventIter {
+        let timeout = if self.async { Some(Duration::from_millis(0)) } else { None };
+        self.read_events(timeout)
+    }
+
+    /// Take every pending event without waiting, however many there are
+    pub fn poll_events(&mut self) -> Vec<Event> {
+        self.read_events(Some(Duration::from_millis(0))).events
+    }
+
+    /// Iterator over events, waiting at most `ms` milliseconds for the first one
+    pub fn events_timeout(&mut self, ms: u32) -> EventIter {
+        self.read_events(Some(Duration::from_millis(ms as u64)))
+    }
+
+    fn read_events(&mut self, timeout: Option<Duration>) -> EventIter {
+        let start = Instant::now();
         let mut iter = EventIter {
-            events: [Event::new(); 128],
+            events: Vec::new(),
             i: 0,
-            count: 0,
         };
 
+        let mut buf = [Event::new(); 128];
         'blocking: loop {
-            //Should it be cleared? iter.events = [Event::new(); 128];
             match self.file.read(unsafe {
-                slice::from_raw_parts_mut(iter.events.as_mut_ptr() as *mut u8, iter.events.len() * mem::size_of::<Event>())
+                slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, buf.len() * mem::size_of::<Event>())
             }){
-                Ok(0) => if ! self.async {
-                    thread::yield_now();
-                } else {
+                Ok(0) => if ! iter.events.is_empty() {
                     break 'blocking;
+                } else {
+                    match timeout {
+                        Some(timeout) if start.elapsed() >= timeout => break 'blocking,
+                        _ => thread::yield_now()
+                    }
                 },
                 Ok(count) => {
-                    iter.count = count/mem::size_of::<Event>();
-                    break 'blocking;
+                    let count = count/mem::size_of::<Event>();
+                    for event in buf[..count].iter() {
+                        let mut extra = Vec::new();
+                        if event.code == EVENT_MOVE {
+                            self.x = event.a as i32;
+                            self.y = event.b as i32;
+                        } else if event.code == EVENT_MOUSE {
+                            let mouse = MouseEvent::from_event(*event);
+                            // Orbital does not report motion separately, so derive it from the last position
+                            if mouse.x != self.mouse.x || mouse.y != self.mouse.y {
+                                extra.push(MouseRelativeEvent {
+                                    dx: mouse.x - self.mouse.x,
+                                    dy: mouse.y - self.mouse.y
+                                }.to_event());
+                            }
+                            // Orbital only reports button state, so report a button event whenever it changes
+                            if mouse.left_button != self.mouse.left_button || mouse.middle_button != self.mouse.middle_button || mouse.right_button != self.mouse.right_button {
+                                extra.push(ButtonEvent {
+                                    left: mouse.left_button,
+                                    middle: mouse.middle_button,
+                                    right: mouse.right_button
+                                }.to_event());
+                            }
+                            // Look for buttons that were just pressed
+                            let double_click = if mouse.left_button && ! self.mouse.left_button {
+                                self.double_click(mouse.x, mouse.y, BUTTON_LEFT)
+                            } else if mouse.middle_button && ! self.mouse.middle_button {
+                                self.double_click(mouse.x, mouse.y, BUTTON_MIDDLE)
+                            } else if mouse.right_button && ! self.mouse.right_button {
+                                self.double_click(mouse.x, mouse.y, BUTTON_RIGHT)
+                            } else {
+                                None
+                            };
+                            extra.extend(double_click);
+                            self.mouse = mouse;
+                        } else if event.code == EVENT_QUIT {
+                            self.closing = true;
+                        } else if event.code == EVENT_KEY {
+                            let key_event = KeyEvent::from_event(*event);
+                            self.keys[key_event.scancode as usize] = key_event.pressed;
+                        }
+                        iter.events.push(*event);
+                        iter.events.extend(extra);
+                    }
+                    // A full buffer means more events may be waiting
+                    if count < buf.len() {
+                        break 'blocking;
+                    }
                 },
                 Err(_) => break 'blocking,
             }
@@ -179,6 +578,20 @@ impl Window {
     }
 }
 
+/// Split a window path of the form `orbital:flags/x/y/w/h/title` into its position, size and title. Missing or invalid numbers are 0
+fn parse_path(path: &[u8]) -> (i32, i32, u32, u32, String) {
+    // The path is cut off at the end of the buffer, possibly in the middle of a character of a long title
+    let path = String::from_utf8_lossy(path);
+    let mut parts = path.split('/').skip(1);
+    let x = parts.next().and_then(|x| x.parse::<i32>().ok()).unwrap_or(0);
+    let y = parts.next().and_then(|y| y.parse::<i32>().ok()).unwrap_or(0);
+    let w = parts.next().and_then(|w| w.parse::<u32>().ok()).unwrap_or(0);
+    let h = parts.next().and_then(|h| h.parse::<u32>().ok()).unwrap_or(0);
+    // The title may itself contain slashes, so take the remainder
+    let title = parts.collect::<Vec<&str>>().join("/");
+    (x, y, w, h, title)
+}
+
 impl Drop for Window {
     fn drop(&mut self) {
         let _ = unsafe { syscall::funmap(self.data.as_ptr() as usize) };
@@ -193,23 +606,47 @@ impl AsRawFd for Window {
 
 /// Event iterator
 pub struct EventIter {
-    events: [Event; 128],
+    events: Vec<Event>,
     i: usize,
-    count: usize,
 }
 
 impl Iterator for EventIter {
     type Item = Event;
     fn next(&mut self) -> Option<Event> {
-        if self.i < self.count {
-            if let Some(event) = self.events.get(self.i) {
-                self.i += 1;
-                Some(*event)
-            } else {
-                None
-            }
+        if let Some(event) = self.events.get(self.i) {
+            self.i += 1;
+            Some(*event)
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_path;
+
+    #[test]
+    fn parse_path_ascii() {
+        assert_eq!(parse_path(b"orbital:/10/-20/300/400/Hello/World"), (10, -20, 300, 400, "Hello/World".to_string()));
+    }
+
+    #[test]
+    fn parse_path_multibyte() {
+        let path = "orbital:r/1/2/3/4/Café ☕ 🎉 日本語";
+        assert_eq!(parse_path(path.as_bytes()), (1, 2, 3, 4, "Café ☕ 🎉 日本語".to_string()));
+    }
+
+    #[test]
+    fn parse_path_invalid_utf8() {
+        // A title cut off in the middle of a character, as happens when the path fills the buffer
+        let mut path = b"orbital:/1/2/3/4/caf".to_vec();
+        path.extend_from_slice(&"é🎉".as_bytes()[..4]);
+        assert_eq!(parse_path(&path), (1, 2, 3, 4, "caf\u{e9}\u{fffd}".to_string()));
+    }
+
+    #[test]
+    fn parse_path_missing() {
+        assert_eq!(parse_path(b"orbital:/1/x"), (1, 0, 0, 0, String::new()));
+    }
+}
@@ -1,6 +1,8 @@
-use std::cmp;
+use std::{cmp, io, mem};
 
 use FONT;
+use bmp;
+use canvas::Canvas;
 use color::Color;
 use graphicspath::GraphicsPath;
 use graphicspath::PointType;
@@ -29,6 +31,205 @@ pub unsafe fn fast_set32(dst: *mut u32, src: u32, len: usize) {
         : "intel", "volatile");
 }
 
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline(always)]
+#[cold]
+pub unsafe fn fast_set32(dst: *mut u32, src: u32, len: usize) {
+    let data = ::std::slice::from_raw_parts_mut(dst, len);
+    for value in data.iter_mut() {
+        *value = src;
+    }
+}
+
+/// Number of line segments used for each span of a spline
+const SPLINE_STEPS: u32 = 16;
+
+/// Most line segments used to approximate a curve, so control points far off the window do not make drawing take forever
+const MAX_CURVE_STEPS: u32 = 4096;
+
+/// Number of line segments used to approximate a Bézier curve, one for roughly every two pixels of its control polygon
+fn bezier_steps(points: &[[i32; 2]]) -> u32 {
+    let mut len = 0.0;
+    for i in 0..points.len() - 1 {
+        let dx = points[i + 1][0] as f64 - points[i][0] as f64;
+        let dy = points[i + 1][1] as f64 - points[i][1] as f64;
+        len += (dx * dx + dy * dy).sqrt();
+    }
+    cmp::max(1, (len / 2.0).ceil().min(MAX_CURVE_STEPS as f64) as u32)
+}
+
+/// Box blur `len` pixels spaced `step` apart in place, repeating the edge pixels past the ends. `line` is scratch space
+fn blur_line(data: &mut [Color], len: usize, step: usize, radius: usize, line: &mut Vec<u32>) {
+    line.clear();
+    for i in 0..len {
+        line.push(data[i * step].data);
+    }
+
+    let size = radius as u64 * 2 + 1;
+    let at = |i: isize| line[cmp::max(0, cmp::min(len as isize - 1, i)) as usize];
+
+    let mut sums = [0u64; 4];
+    for i in 0..size as isize {
+        let value = at(i - radius as isize);
+        for channel in 0..4 {
+            sums[channel] += ((value >> (channel * 8)) & 0xFF) as u64;
+        }
+    }
+
+    for i in 0..len {
+        data[i * step].data = {
+            let average = |channel: usize| (sums[channel] / size) as u32;
+            average(0) | average(1) << 8 | average(2) << 16 | average(3) << 24
+        };
+
+        let added = at(i as isize + radius as isize + 1);
+        let removed = at(i as isize - radius as isize);
+        for channel in 0..4 {
+            sums[channel] += ((added >> (channel * 8)) & 0xFF) as u64;
+            sums[channel] -= ((removed >> (channel * 8)) & 0xFF) as u64;
+        }
+    }
+}
+
+const CLIP_LEFT: u8 = 1;
+const CLIP_RIGHT: u8 = 2;
+const CLIP_TOP: u8 = 4;
+const CLIP_BOTTOM: u8 = 8;
+
+/// Map a destination offset `d` along one axis of a nine-patch `size` pixels long to a source offset, keeping the first `low` and last `high` of `src_size` pixels fixed and stretching the middle
+fn nine_patch_offset(d: u32, size: u32, src_size: u32, low: u32, high: u32) -> Option<u32> {
+    if d < low {
+        Some(d)
+    } else if d >= size.saturating_sub(high) {
+        Some(src_size - (size - d))
+    } else {
+        let src_mid = src_size - low - high;
+        let dst_mid = size - low - high;
+        if src_mid == 0 {
+            None
+        } else {
+            Some(low + (d - low) * src_mid / dst_mid)
+        }
+    }
+}
+
+/// Whether the direction from the origin to (dx, dy) is between two angles in degrees, measured clockwise on screen from the positive x-axis. The range wraps around past 360 when `end_angle` is less than `start_angle`
+fn angle_between(dx: i32, dy: i32, start_angle: f32, end_angle: f32) -> bool {
+    if (end_angle - start_angle).abs() >= 360.0 {
+        return true;
+    }
+    let normalize = |angle: f32| ((angle % 360.0) + 360.0) % 360.0;
+    let start = normalize(start_angle);
+    let span = normalize(end_angle - start_angle);
+    // Screen y points down, so atan2 already increases clockwise
+    let angle = normalize((dy as f32).atan2(dx as f32).to_degrees());
+    normalize(angle - start) <= span
+}
+
+/// Scale the alpha of a color by how much of a pixel an anti-aliased shape covers, from 0.0 to 1.0
+fn with_coverage(color: Color, amount: f64) -> Color {
+    Color::rgba(color.r(), color.g(), color.b(), (color.a() as f64 * amount) as u8)
+}
+
+/// Step along a line with Bresenham's algorithm, calling `plot` for each pixel from (x1, y1) to (x2, y2) inclusive. The line should already be clipped
+fn step_line<F: FnMut(i32, i32)>(x1: i32, y1: i32, x2: i32, y2: i32, mut plot: F) {
+    let mut x = x1;
+    let mut y = y1;
+
+    let dx = if x1 > x2 { x1 - x2 } else { x2 - x1 };
+    let dy = if y1 > y2 { y1 - y2 } else { y2 - y1 };
+
+    let sx = if x1 < x2 { 1 } else { -1 };
+    let sy = if y1 < y2 { 1 } else { -1 };
+
+    let mut err = if dx > dy { dx } else {-dy} / 2;
+    let mut err_tolerance;
+
+    loop {
+        plot(x, y);
+
+        if x == x2 && y == y2 { break };
+
+        err_tolerance = 2 * err;
+
+        if err_tolerance > -dx { err -= dy; x += sx; }
+        if err_tolerance < dy { err += dx; y += sy; }
+    }
+}
+
+/// Which sides of the inclusive rectangle (x1, y1, x2, y2) a point lies beyond
+fn clip_code(bounds: (i64, i64, i64, i64), x: i64, y: i64) -> u8 {
+    let mut code = 0;
+    if x < bounds.0 {
+        code |= CLIP_LEFT;
+    } else if x > bounds.2 {
+        code |= CLIP_RIGHT;
+    }
+    if y < bounds.1 {
+        code |= CLIP_TOP;
+    } else if y > bounds.3 {
+        code |= CLIP_BOTTOM;
+    }
+    code
+}
+
+/// Trim a line to the inclusive rectangle (x1, y1, x2, y2) using Cohen–Sutherland, or return `None` if none of it is inside
+fn clip_line(bounds: (i32, i32, i32, i32), x1: i32, y1: i32, x2: i32, y2: i32) -> Option<(i32, i32, i32, i32)> {
+    if bounds.0 > bounds.2 || bounds.1 > bounds.3 {
+        return None;
+    }
+
+    // Widen so the intersection math cannot overflow
+    let bounds = (bounds.0 as i64, bounds.1 as i64, bounds.2 as i64, bounds.3 as i64);
+    let (mut x1, mut y1, mut x2, mut y2) = (x1 as i64, y1 as i64, x2 as i64, y2 as i64);
+    let mut code1 = clip_code(bounds, x1, y1);
+    let mut code2 = clip_code(bounds, x2, y2);
+
+    loop {
+        if code1 | code2 == 0 {
+            return Some((x1 as i32, y1 as i32, x2 as i32, y2 as i32));
+        } else if code1 & code2 != 0 {
+            return None;
+        }
+
+        // The product can need more than 64 bits, so scale in floating point, truncating like integer division
+        let scale = |delta: i64, num: i64, den: i64| (delta as f64 * num as f64 / den as f64) as i64;
+        let code = if code1 != 0 { code1 } else { code2 };
+        let (x, y) = if code & CLIP_TOP != 0 {
+            (x1 + scale(x2 - x1, bounds.1 - y1, y2 - y1), bounds.1)
+        } else if code & CLIP_BOTTOM != 0 {
+            (x1 + scale(x2 - x1, bounds.3 - y1, y2 - y1), bounds.3)
+        } else if code & CLIP_LEFT != 0 {
+            (bounds.0, y1 + scale(y2 - y1, bounds.0 - x1, x2 - x1))
+        } else {
+            (bounds.2, y1 + scale(y2 - y1, bounds.2 - x1, x2 - x1))
+        };
+
+        if code == code1 {
+            x1 = x;
+            y1 = y;
+            code1 = clip_code(bounds, x1, y1);
+        } else {
+            x2 = x;
+            y2 = y;
+            code2 = clip_code(bounds, x2, y2);
+        }
+    }
+}
+
+/// A color adjustment for `Renderer::adjust_region`. Alpha is left unchanged
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Adjust {
+    /// Add to each channel, darkening if negative
+    Brightness(i16),
+    /// Scale each channel's distance from the middle gray, reducing contrast if less than 1.0
+    Contrast(f32),
+    /// Invert each channel
+    Invert,
+    /// Replace each pixel with its luminance
+    Grayscale,
+}
+
 pub trait Renderer {
     /// Get width
     fn width(&self) -> u32;
@@ -39,24 +240,88 @@ pub trait Renderer {
     /// Access the pixel buffer
     fn data(&self) -> &[Color];
 
-    /// Access the pixel buffer mutably
+    /// Access the pixel buffer mutably, row by row from the top left
     fn data_mut(&mut self) -> &mut [Color];
 
+    /// Get the number of pixels from the start of one row to the next, which may be more than the width
+    fn stride(&self) -> u32 {
+        self.width()
+    }
+
     /// Flip the buffer
     fn sync(&mut self) -> bool;
 
-    /// Draw a pixel
-    fn pixel(&mut self, x: i32, y: i32, color: Color) {
+    /// Get the clipping rectangle, if any. Drawing outside of it is discarded
+    fn clip(&self) -> Option<(i32, i32, u32, u32)> {
+        None
+    }
+
+    /// Get the drawable region as (x1, y1, x2, y2), which is the buffer intersected with the clipping rectangle
+    fn clip_bounds(&self) -> (i32, i32, i32, i32) {
+        let w = self.width() as i32;
+        let h = self.height() as i32;
+        match self.clip() {
+            Some((x, y, clip_w, clip_h)) => (
+                cmp::max(0, x),
+                cmp::max(0, y),
+                cmp::min(w, x + clip_w as i32),
+                cmp::min(h, y + clip_h as i32)
+            ),
+            None => (0, 0, w, h)
+        }
+    }
+
+    /// Get the bitmap font used to draw text and the height of its glyphs in pixels. Glyphs are 8 pixels wide with one byte per row, and are stored in character order
+    fn font(&self) -> (&[u8], usize) {
+        (FONT, 16)
+    }
+
+    /// Save the pixel buffer as a 32-bit BMP file
+    fn save_bmp(&self, path: &str) -> io::Result<()> {
+        if self.stride() == self.width() {
+            bmp::save(path, self.width(), self.height(), self.data())
+        } else {
+            bmp::save(path, self.width(), self.height(), &self.screenshot())
+        }
+    }
+
+    /// Copy the pixel buffer into exactly `width * height` colors, row by row without any padding between rows. `Canvas::from_data` can turn it back into something to draw
+    fn screenshot(&self) -> Vec<Color> {
+        let w = self.width() as usize;
+        let h = self.height() as usize;
+        let stride = cmp::max(1, self.stride() as usize);
+        let mut packed = Vec::with_capacity(w * h);
+        for row in self.data().chunks(stride).take(h) {
+            packed.extend_from_slice(&row[..cmp::min(w, row.len())]);
+        }
+        packed.resize(w * h, Color::rgba(0, 0, 0, 0));
+        packed
+    }
+
+    /// Get the color of a pixel, or a transparent color if it is outside of the buffer
+    fn get_pixel(&self, x: i32, y: i32) -> Color {
         let w = self.width();
         let h = self.height();
+        if x >= 0 && y >= 0 && x < w as i32 && y < h as i32 {
+            if let Some(color) = self.data().get(y as usize * self.stride() as usize + x as usize) {
+                return *color;
+            }
+        }
+        Color::rgba(0, 0, 0, 0)
+    }
+
+    /// Draw a pixel
+    fn pixel(&mut self, x: i32, y: i32, color: Color) {
+        let stride = self.stride();
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
         let data = self.data_mut();
 
-        if x >= 0 && y >= 0 && x < w as i32 && y < h as i32 {
+        if x >= clip_x1 && y >= clip_y1 && x < clip_x2 && y < clip_y2 {
             let new = color.data;
 
             let alpha = (new >> 24) & 0xFF;
             if alpha > 0 {
-                let old = &mut data[y as usize * w as usize + x as usize].data;
+                let old = &mut data[y as usize * stride as usize + x as usize].data;
                 if alpha >= 255 {
                     *old = new;
                 } else {
@@ -114,6 +379,49 @@ pub trait Renderer {
         }
     }
 
+    /// Draw the part of a circle's outline between two angles in degrees, measured clockwise from the positive x-axis. If `end_angle` is less than `start_angle`, the arc wraps around through 0
+    fn arc_angles(&mut self, x0: i32, y0: i32, radius: i32, start_angle: f32, end_angle: f32, color: Color) {
+        let mut x = radius.abs();
+        let mut y = 0;
+        let mut err = 0;
+
+        while x >= y {
+            for &(dx, dy) in &[(-x, y), (x, y), (-y, x), (y, x), (-x, -y), (x, -y), (-y, -x), (y, -x)] {
+                if angle_between(dx, dy, start_angle, end_angle) {
+                    self.pixel(x0 + dx, y0 + dy, color);
+                }
+            }
+
+            y += 1;
+            err += 1 + 2*y;
+            if 2*(err-x) + 1 > 0 {
+                x -= 1;
+                err += 1 - 2*x;
+            }
+        }
+    }
+
+    /// Fill the slice of a circle between two angles in degrees, measured clockwise from the positive x-axis, as for a pie chart. If `end_angle` is less than `start_angle`, the slice wraps around through 0
+    fn pie(&mut self, x0: i32, y0: i32, radius: i32, start_angle: f32, end_angle: f32, color: Color) {
+        let radius = radius.abs();
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        let x1 = cmp::max(clip_x1, x0 - radius);
+        let y1 = cmp::max(clip_y1, y0 - radius);
+        let x2 = cmp::min(clip_x2, x0 + radius + 1);
+        let y2 = cmp::min(clip_y2, y0 + radius + 1);
+
+        for y in y1..y2 {
+            let dy = y - y0;
+            for x in x1..x2 {
+                let dx = x - x0;
+                // Within half a pixel of the edge, since (r + 0.5)^2 rounds down to r^2 + r
+                if dx * dx + dy * dy <= radius * radius + radius && angle_between(dx, dy, start_angle, end_angle) {
+                    self.pixel(x, y, color);
+                }
+            }
+        }
+    }
+
     /// Draw a circle. Negative radius will fill in the inside
     fn circle(&mut self, x0: i32, y0: i32, radius: i32, color: Color) {
         let mut x = radius.abs();
@@ -148,29 +456,235 @@ pub trait Renderer {
         }
     }
 
+    /// Draw an anti-aliased circle outline using Xiaolin Wu's algorithm. This is slower than `circle`
+    fn wu_circle(&mut self, x0: i32, y0: i32, radius: i32, color: Color) {
+        let radius = radius.abs();
+        if radius == 0 {
+            self.pixel(x0, y0, color);
+            return;
+        }
+
+        let r = radius as f64;
+        let end = (r / 2.0f64.sqrt()).floor() as i32;
+        for x in 0..end + 1 {
+            let y = (r * r - (x * x) as f64).sqrt();
+            let ipart = y.floor() as i32;
+            let fpart = y - ipart as f64;
+            for &(y, amount) in &[(ipart, 1.0 - fpart), (ipart + 1, fpart)] {
+                let pixel_color = with_coverage(color, amount);
+                // Mirror into all eight octants, skipping mirrors that land on the same pixel
+                let swapped = if x == y { 1 } else { 2 };
+                for &(px, py) in [(x, y), (y, x)].iter().take(swapped) {
+                    let signs_x: &[i32] = if px == 0 { &[1] } else { &[1, -1] };
+                    let signs_y: &[i32] = if py == 0 { &[1] } else { &[1, -1] };
+                    for sx in signs_x {
+                        for sy in signs_y {
+                            self.pixel(x0 + sx * px, y0 + sy * py, pixel_color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draw an ellipse with horizontal radius `a` and vertical radius `b`. Negative radii will fill in the inside
+    fn ellipse(&mut self, x0: i32, y0: i32, a: i32, b: i32, color: Color) {
+        let filled = a < 0 || b < 0;
+        let a = a.abs();
+        let b = b.abs();
+
+        if a == 0 || b == 0 {
+            self.line(x0 - a, y0 - b, x0 + a, y0 + b, color);
+            return;
+        }
+
+        let a2 = a as i64 * a as i64;
+        let b2 = b as i64 * b as i64;
+
+        let mut x: i64 = 0;
+        let mut y: i64 = b as i64;
+        let mut dx: i64 = 0;
+        let mut dy: i64 = 2 * a2 * y;
+
+        // Decision variables are scaled by 4 to stay in integers
+        let mut err = 4 * b2 - 4 * a2 * b as i64 + a2;
+        let mut region_two = false;
+
+        loop {
+            {
+                let (x, y) = (x as i32, y as i32);
+                if filled {
+                    self.rect(x0 - x, y0 + y, x as u32 * 2 + 1, 1, color);
+                    self.rect(x0 - x, y0 - y, x as u32 * 2 + 1, 1, color);
+                } else {
+                    self.pixel(x0 - x, y0 + y, color);
+                    self.pixel(x0 + x, y0 + y, color);
+                    self.pixel(x0 - x, y0 - y, color);
+                    self.pixel(x0 + x, y0 - y, color);
+                }
+            }
+
+            if ! region_two && dx >= dy {
+                region_two = true;
+                err = b2 * (2 * x + 1) * (2 * x + 1) + 4 * a2 * (y - 1) * (y - 1) - 4 * a2 * b2;
+            }
+
+            if ! region_two {
+                x += 1;
+                dx += 2 * b2;
+                if err < 0 {
+                    err += 4 * (dx + b2);
+                } else {
+                    y -= 1;
+                    dy -= 2 * a2;
+                    err += 4 * (dx - dy + b2);
+                }
+            } else {
+                if y <= 0 {
+                    break;
+                }
+
+                y -= 1;
+                dy -= 2 * a2;
+                if err > 0 {
+                    err += 4 * (a2 - dy);
+                } else {
+                    x += 1;
+                    dx += 2 * b2;
+                    err += 4 * (dx - dy + a2);
+                }
+            }
+        }
+    }
+
     /// Draw a line
     fn line(&mut self, argx1: i32, argy1: i32, argx2: i32, argy2: i32, color: Color) {
-        let mut x = argx1;
-        let mut y = argy1;
+        // Trim to the drawable region first, so far off-screen endpoints cannot overflow the math below
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        let (argx1, argy1, argx2, argy2) = match clip_line((clip_x1, clip_y1, clip_x2 - 1, clip_y2 - 1), argx1, argy1, argx2, argy2) {
+            Some(line) => line,
+            None => return
+        };
 
-        let dx = if argx1 > argx2 { argx1 - argx2 } else { argx2 - argx1 };
-        let dy = if argy1 > argy2 { argy1 - argy2 } else { argy2 - argy1 };
+        if argy1 == argy2 {
+            self.hline(cmp::min(argx1, argx2), argy1, (argx1 - argx2).abs() as u32 + 1, color);
+            return;
+        } else if argx1 == argx2 {
+            self.vline(argx1, cmp::min(argy1, argy2), (argy1 - argy2).abs() as u32 + 1, color);
+            return;
+        }
 
-        let sx = if argx1 < argx2 { 1 } else { -1 };
-        let sy = if argy1 < argy2 { 1 } else { -1 };
+        step_line(argx1, argy1, argx2, argy2, |x, y| self.pixel(x, y, color));
+    }
 
-        let mut err = if dx > dy { dx } else {-dy} / 2;
-        let mut err_tolerance;
+    /// Draw an anti-aliased line using Xiaolin Wu's algorithm
+    fn wu_line(&mut self, argx1: i32, argy1: i32, argx2: i32, argy2: i32, color: Color) {
+        if argx1 == argx2 || argy1 == argy2 {
+            self.line(argx1, argy1, argx2, argy2, color);
+            return;
+        }
 
-        loop {
-            self.pixel(x, y, color);
+        // Trim to a pixel past the drawable region, so the partly covered pixels along the edges are still drawn.
+        // Only the range of the major axis is taken from the trimmed line, so rounding does not change its slope
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        let (cx1, cy1, cx2, cy2) = match clip_line((clip_x1 - 1, clip_y1 - 1, clip_x2, clip_y2), argx1, argy1, argx2, argy2) {
+            Some(line) => line,
+            None => return
+        };
 
-            if x == argx2 && y == argy2 { break };
+        let coverage = |amount: f64| with_coverage(color, amount);
 
-            err_tolerance = 2 * err;
+        let steep = (argy2 as i64 - argy1 as i64).abs() > (argx2 as i64 - argx1 as i64).abs();
+        let (mut x1, mut y1, mut x2, mut y2) = if steep {
+            (argy1 as i64, argx1 as i64, argy2 as i64, argx2 as i64)
+        } else {
+            (argx1 as i64, argy1 as i64, argx2 as i64, argy2 as i64)
+        };
+        if x1 > x2 {
+            mem::swap(&mut x1, &mut x2);
+            mem::swap(&mut y1, &mut y2);
+        }
+        let (start, end) = if steep {
+            (cmp::min(cy1, cy2) as i64, cmp::max(cy1, cy2) as i64)
+        } else {
+            (cmp::min(cx1, cx2) as i64, cmp::max(cx1, cx2) as i64)
+        };
 
-            if err_tolerance > -dx { err -= dy; x += sx; }
-            if err_tolerance < dy { err += dx; y += sy; }
+        let gradient = (y2 - y1) as f64 / (x2 - x1) as f64;
+
+        for x in start..end + 1 {
+            let intery = y1 as f64 + gradient * (x - x1) as f64;
+            let ipart = intery.floor();
+            let fpart = intery - ipart;
+            let (x, y) = (x as i32, ipart as i32);
+            // Endpoints lie exactly on pixel centers
+            if steep {
+                self.pixel(y, x, coverage(1.0 - fpart));
+                if fpart > 0.0 {
+                    self.pixel(y + 1, x, coverage(fpart));
+                }
+            } else {
+                self.pixel(x, y, coverage(1.0 - fpart));
+                if fpart > 0.0 {
+                    self.pixel(x, y + 1, coverage(fpart));
+                }
+            }
+        }
+    }
+
+    /// Draw a line of the given width, with square caps that cover the endpoints
+    fn thick_line(&mut self, argx1: i32, argy1: i32, argx2: i32, argy2: i32, width: u32, color: Color) {
+        if width == 0 {
+            return;
+        } else if width == 1 {
+            self.line(argx1, argy1, argx2, argy2, color);
+            return;
+        }
+
+        let dx = argx2 as f64 - argx1 as f64;
+        let dy = argy2 as f64 - argy1 as f64;
+        let len = (dx * dx + dy * dy).sqrt();
+        // Unit direction, half a pixel long so the caps reach past the endpoints
+        let (ux, uy) = if len > 0.0 { (dx / len * 0.5, dy / len * 0.5) } else { (0.5, 0.0) };
+        // Perpendicular offset, half the width long
+        let (nx, ny) = (-uy * width as f64, ux * width as f64);
+
+        let corners = [
+            (argx1 as f64 - ux + nx, argy1 as f64 - uy + ny),
+            (argx2 as f64 + ux + nx, argy2 as f64 + uy + ny),
+            (argx2 as f64 + ux - nx, argy2 as f64 + uy - ny),
+            (argx1 as f64 - ux - nx, argy1 as f64 - uy - ny),
+        ];
+
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        let min_y = corners.iter().fold(f64::MAX, |acc, c| acc.min(c.1)).max(clip_y1 as f64);
+        let max_y = corners.iter().fold(f64::MIN, |acc, c| acc.max(c.1)).min(clip_y2 as f64);
+
+        // Fill pixels whose centers lie inside the quad, one span per scanline
+        for y in min_y.ceil() as i32..max_y.ceil() as i32 {
+            let fy = y as f64;
+            let mut min_x = f64::MAX;
+            let mut max_x = f64::MIN;
+            for i in 0..corners.len() {
+                let (ax, ay) = corners[i];
+                let (bx, by) = corners[(i + 1) % corners.len()];
+                if (ay <= fy && fy <= by) || (by <= fy && fy <= ay) {
+                    let x = if ay == by { ax.min(bx) } else { ax + (fy - ay) * (bx - ax) / (by - ay) };
+                    min_x = min_x.min(x);
+                    max_x = max_x.max(x);
+                    if ay == by {
+                        max_x = max_x.max(ax.max(bx));
+                    }
+                }
+            }
+
+            if min_x <= max_x {
+                let start = min_x.ceil().max(clip_x1 as f64) as i32;
+                let end = max_x.ceil().min(clip_x2 as f64) as i32;
+                if end > start {
+                    self.rect(start, y, (end - start) as u32, 1, color);
+                }
+            }
         }
     }
 
@@ -186,6 +700,173 @@ pub trait Renderer {
         }
     }
 
+    /// Draw connected lines, fading from the color of each point to the color of the next. If there are fewer colors than points, the last color is used for the remaining points, and nothing is drawn without any colors
+    fn lines_gradient(&mut self, points: &[[i32; 2]], colors: &[Color]) {
+        if colors.is_empty() {
+            return;
+        }
+        let color_at = |i: usize| colors[cmp::min(i, colors.len() - 1)];
+
+        if points.len() == 1 {
+            self.pixel(points[0][0], points[0][1], color_at(0));
+        }
+
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        for i in 0..points.len().saturating_sub(1) {
+            let (start, end) = (color_at(i), color_at(i + 1));
+            let (x1, y1) = (points[i][0], points[i][1]);
+            let (x2, y2) = (points[i + 1][0], points[i + 1][1]);
+            let (cx1, cy1, cx2, cy2) = match clip_line((clip_x1, clip_y1, clip_x2 - 1, clip_y2 - 1), x1, y1, x2, y2) {
+                Some(line) => line,
+                None => continue
+            };
+
+            // Measure progress along the whole segment, so the colors stay in place when it is clipped
+            let steps = cmp::max((x2 as i64 - x1 as i64).abs(), (y2 as i64 - y1 as i64).abs());
+            step_line(cx1, cy1, cx2, cy2, |x, y| {
+                let step = cmp::max((x as i64 - x1 as i64).abs(), (y as i64 - y1 as i64).abs());
+                let scale = if steps > 0 { step as f64 / steps as f64 } else { 0.0 };
+                self.pixel(x, y, Color::interpolate(start, end, scale));
+            });
+        }
+    }
+
+    /// Draw the outline of a polygon, closing it back to the first point
+    fn polygon(&mut self, points: &[[i32; 2]], color: Color) {
+        self.lines(points, color);
+        if points.len() > 2 {
+            let first = points[0];
+            let last = points[points.len() - 1];
+            self.line(last[0], last[1], first[0], first[1], color);
+        }
+    }
+
+    /// Fill a polygon using the even-odd rule, covering the pixels whose centers lie inside
+    fn polygon_fill(&mut self, points: &[[i32; 2]], color: Color) {
+        if points.len() < 3 {
+            self.lines(points, color);
+            return;
+        }
+
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        let min_y = cmp::max(clip_y1, points.iter().map(|point| point[1]).min().unwrap_or(0));
+        let max_y = cmp::min(clip_y2, points.iter().map(|point| point[1]).max().unwrap_or(0));
+
+        let mut crossings = Vec::new();
+        for y in min_y..max_y {
+            crossings.clear();
+            for i in 0..points.len() {
+                let a = points[i];
+                let b = points[(i + 1) % points.len()];
+                // Half-open so shared vertices are only counted once
+                if (a[1] <= y && y < b[1]) || (b[1] <= y && y < a[1]) {
+                    crossings.push(a[0] as f64 + (y as f64 - a[1] as f64) * (b[0] as f64 - a[0] as f64) / (b[1] as f64 - a[1] as f64));
+                }
+            }
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal));
+
+            for pair in crossings.chunks(2) {
+                if pair.len() == 2 {
+                    let start = pair[0].ceil().max(clip_x1 as f64) as i32;
+                    let end = pair[1].ceil().min(clip_x2 as f64) as i32;
+                    if end > start {
+                        self.rect(start, y, (end - start) as u32, 1, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fill a triangle, covering the pixels whose centers lie inside
+    fn triangle(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, x3: i32, y3: i32, color: Color) {
+        // Sort the vertices from top to bottom
+        let mut vertices = [(x1, y1), (x2, y2), (x3, y3)];
+        vertices.sort_by_key(|vertex| vertex.1);
+        let (top, middle, bottom) = (vertices[0], vertices[1], vertices[2]);
+
+        let edge_x = |a: (i32, i32), b: (i32, i32), y: i32| -> f64 {
+            a.0 as f64 + (y as f64 - a.1 as f64) * (b.0 as f64 - a.0 as f64) / (b.1 as f64 - a.1 as f64)
+        };
+
+        // Split into a flat-bottomed upper half and a flat-topped lower half
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        for y in cmp::max(clip_y1, top.1)..cmp::min(clip_y2, bottom.1) {
+            let long = edge_x(top, bottom, y);
+            let short = if y < middle.1 {
+                edge_x(top, middle, y)
+            } else {
+                edge_x(middle, bottom, y)
+            };
+
+            let start = long.min(short).ceil().max(clip_x1 as f64) as i32;
+            let end = long.max(short).ceil().min(clip_x2 as f64) as i32;
+            if end > start {
+                self.rect(start, y, (end - start) as u32, 1, color);
+            }
+        }
+    }
+
+    /// Draw a quadratic Bézier curve, subdivided according to the length of its control polygon
+    fn bezier_quad(&mut self, p0: [i32; 2], p1: [i32; 2], p2: [i32; 2], color: Color) {
+        let steps = bezier_steps(&[p0, p1, p2]);
+        let mut points = Vec::with_capacity(steps as usize + 1);
+        for i in 0..steps + 1 {
+            let t = i as f64 / steps as f64;
+            let u = 1.0 - t;
+            let point = |axis: usize| -> i32 {
+                (u * u * p0[axis] as f64 + 2.0 * u * t * p1[axis] as f64 + t * t * p2[axis] as f64).round() as i32
+            };
+            points.push([point(0), point(1)]);
+        }
+        self.lines(&points, color);
+    }
+
+    /// Draw a cubic Bézier curve, subdivided according to the length of its control polygon
+    fn bezier_cubic(&mut self, p0: [i32; 2], p1: [i32; 2], p2: [i32; 2], p3: [i32; 2], color: Color) {
+        let steps = bezier_steps(&[p0, p1, p2, p3]);
+        let mut points = Vec::with_capacity(steps as usize + 1);
+        for i in 0..steps + 1 {
+            let t = i as f64 / steps as f64;
+            let u = 1.0 - t;
+            let point = |axis: usize| -> i32 {
+                (u * u * u * p0[axis] as f64 + 3.0 * u * u * t * p1[axis] as f64 +
+                 3.0 * u * t * t * p2[axis] as f64 + t * t * t * p3[axis] as f64).round() as i32
+            };
+            points.push([point(0), point(1)]);
+        }
+        self.lines(&points, color);
+    }
+
+    /// Draw a Catmull-Rom spline passing through every point. The ends are extended by repeating the first and last points
+    fn spline(&mut self, points: &[[i32; 2]], color: Color) {
+        if points.len() < 3 {
+            self.lines(points, color);
+            return;
+        }
+
+        // Use fewer segments per span for long splines, down to one
+        let spans = points.len() - 1;
+        let steps = cmp::max(1, cmp::min(SPLINE_STEPS as usize, MAX_CURVE_STEPS as usize / spans)) as u32;
+        let mut curve = Vec::with_capacity(spans * steps as usize + 1);
+        curve.push(points[0]);
+        for i in 0..spans {
+            let p0 = points[if i > 0 { i - 1 } else { i }];
+            let p1 = points[i];
+            let p2 = points[i + 1];
+            let p3 = points[cmp::min(i + 2, points.len() - 1)];
+            for step in 1..steps + 1 {
+                let t = step as f64 / steps as f64;
+                let point = |axis: usize| -> i32 {
+                    let (a, b, c, d) = (p0[axis] as f64, p1[axis] as f64, p2[axis] as f64, p3[axis] as f64);
+                    (0.5 * (2.0 * b + (c - a) * t + (2.0 * a - 5.0 * b + 4.0 * c - d) * t * t +
+                            (3.0 * b - a - 3.0 * c + d) * t * t * t)).round() as i32
+                };
+                curve.push([point(0), point(1)]);
+            }
+        }
+        self.lines(&curve, color);
+    }
+
     /// Draw a path (GraphicsPath)
     fn draw_path_stroke(&mut self, graphicspath: GraphicsPath, color: Color) {
         let mut x: i32 = 0;
@@ -203,17 +884,61 @@ pub trait Renderer {
 
     /// Draw a character, using the loaded font
     fn char(&mut self, x: i32, y: i32, c: char, color: Color) {
-        let mut offset = (c as usize) * 16;
-        for row in 0..16 {
-            let row_data;
-            if offset < FONT.len() {
-                row_data = FONT[offset];
-            } else {
-                row_data = 0;
+        let height = self.font().1;
+        let mut offset = (c as usize) * height;
+        for row in 0..height {
+            let row_data = self.font().0.get(offset).cloned().unwrap_or(0);
+
+            for col in 0..8 {
+                let pixel = (row_data >> (7 - col)) & 1;
+                if pixel > 0 {
+                    self.pixel(x + col as i32, y + row as i32, color);
+                }
             }
+            offset += 1;
+        }
+    }
+
+    /// Draw a character over a filled background cell, using the loaded font
+    fn char_bg(&mut self, x: i32, y: i32, c: char, fg: Color, bg: Color) {
+        let height = self.font().1;
+        self.rect(x, y, 8, height as u32, bg);
+        self.char(x, y, c, fg);
+    }
+
+    /// Draw a character scaled up by an integer factor, using the loaded font
+    fn char_scaled(&mut self, x: i32, y: i32, c: char, color: Color, scale: u32) {
+        if scale == 1 {
+            self.char(x, y, c, color);
+            return;
+        }
+
+        let height = self.font().1;
+        let mut offset = (c as usize) * height;
+        for row in 0..height {
+            let row_data = self.font().0.get(offset).cloned().unwrap_or(0);
 
             for col in 0..8 {
                 let pixel = (row_data >> (7 - col)) & 1;
+                if pixel > 0 {
+                    self.rect(x + (col * scale) as i32, y + (row as u32 * scale) as i32, scale, scale, color);
+                }
+            }
+            offset += 1;
+        }
+    }
+
+    /// Draw a character in bold by thickening it one pixel to the right, using the loaded font. The result is 9 pixels wide, one more than the 8 pixel cell of `char`
+    fn char_bold(&mut self, x: i32, y: i32, c: char, color: Color) {
+        let height = self.font().1;
+        let mut offset = (c as usize) * height;
+        for row in 0..height {
+            let row_data = self.font().0.get(offset).cloned().unwrap_or(0);
+
+            // Each pixel also covers the one to its right, drawn once so translucent colors blend evenly
+            let bold_data = (row_data as u16) << 1 | row_data as u16;
+            for col in 0..9 {
+                let pixel = (bold_data >> (8 - col)) & 1;
                 if pixel > 0 {
                     self.pixel(x + col as i32, y + row as i32, color);
                 }
@@ -222,6 +947,146 @@ pub trait Renderer {
         }
     }
 
+    /// Draw a string in bold, like `string` but with `char_bold`. Each character advances 9 pixels instead of 8 so that the extra pixel does not run into the next one
+    fn string_bold(&mut self, x: i32, y: i32, text: &str, color: Color) {
+        let height = self.font().1 as i32;
+        let mut col = 0;
+        let mut row = 0;
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    col = 0;
+                    row += 1;
+                },
+                '\t' => col = (col / 8 + 1) * 8,
+                _ => {
+                    self.char_bold(x + col * 9, y + row * height, c, color);
+                    col += 1;
+                }
+            }
+        }
+    }
+
+    /// Draw a string, using the loaded font. Newlines start a new row and tabs align to 8 columns
+    fn string(&mut self, x: i32, y: i32, text: &str, color: Color) {
+        let height = self.font().1 as i32;
+        let mut col = 0;
+        let mut row = 0;
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    col = 0;
+                    row += 1;
+                },
+                '\t' => col = (col / 8 + 1) * 8,
+                _ => {
+                    self.char(x + col * 8, y + row * height, c, color);
+                    col += 1;
+                }
+            }
+        }
+    }
+
+    /// Draw a string like `string`, optionally underlined just below the baseline and struck through at the height of a hyphen, as placed in the built-in font. The lines span the width of each row of text
+    fn string_styled(&mut self, x: i32, y: i32, text: &str, color: Color, underline: bool, strike: bool) {
+        self.string(x, y, text, color);
+        if ! underline && ! strike {
+            return;
+        }
+
+        let height = self.font().1 as i32;
+        for (row, line) in text.split('\n').enumerate() {
+            let (w, _) = self.text_size(line);
+            let row_y = y + row as i32 * height;
+            if underline {
+                self.hline(x, row_y + height - 2, w, color);
+            }
+            if strike {
+                self.hline(x, row_y + height * 9 / 16, w, color);
+            }
+        }
+    }
+
+    /// Get the size in pixels that `string` would use to draw some text
+    fn text_size(&self, text: &str) -> (u32, u32) {
+        if text.is_empty() {
+            return (0, 0);
+        }
+
+        let mut cols = 0;
+        let mut rows = 1;
+        let mut col = 0;
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    col = 0;
+                    rows += 1;
+                },
+                '\t' => col = (col / 8 + 1) * 8,
+                _ => col += 1
+            }
+            cols = cmp::max(cols, col);
+        }
+
+        (cols * 8, rows * self.font().1 as u32)
+    }
+
+    /// Replace the connected region of the color at (x, y) with a new color
+    fn flood_fill(&mut self, x: i32, y: i32, color: Color) {
+        let stride = self.stride() as i32;
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        if x < clip_x1 || y < clip_y1 || x >= clip_x2 || y >= clip_y2 {
+            return;
+        }
+
+        let data = self.data_mut();
+        let target = data[(y * stride + x) as usize].data;
+        if target == color.data {
+            return;
+        }
+
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            let row = (y * stride) as usize;
+            if data[row + x as usize].data != target {
+                continue;
+            }
+
+            // Extend the span in both directions and fill it
+            let mut left = x;
+            while left > clip_x1 && data[row + left as usize - 1].data == target {
+                left -= 1;
+            }
+            let mut right = x;
+            while right + 1 < clip_x2 && data[row + right as usize + 1].data == target {
+                right += 1;
+            }
+            for i in left..right + 1 {
+                data[row + i as usize] = color;
+            }
+
+            // Queue the start of each matching span above and below
+            for &next_y in [y - 1, y + 1].iter() {
+                if next_y < clip_y1 || next_y >= clip_y2 {
+                    continue;
+                }
+
+                let next_row = (next_y * stride) as usize;
+                let mut in_span = false;
+                for i in left..right + 1 {
+                    if data[next_row + i as usize].data == target {
+                        if ! in_span {
+                            stack.push((i, next_y));
+                            in_span = true;
+                        }
+                    } else {
+                        in_span = false;
+                    }
+                }
+            }
+        }
+    }
+
     /// Set entire window to a color
     fn set(&mut self, color: Color) {
         let data = self.data_mut();
@@ -237,14 +1102,17 @@ pub trait Renderer {
 
     /// Draw rectangle
     fn rect(&mut self, x: i32, y: i32, w: u32, h: u32, color: Color) {
-        let self_w = self.width();
-        let self_h = self.height();
+        let stride = self.stride();
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
 
-        let start_y = cmp::max(0, cmp::min(self_h as i32 - 1, y));
-        let end_y = cmp::max(start_y, cmp::min(self_h as i32, y + h as i32));
+        let start_y = cmp::max(clip_y1, y);
+        let end_y = cmp::max(start_y, cmp::min(clip_y2, y + h as i32));
 
-        let start_x = cmp::max(0, cmp::min(self_w as i32 - 1, x));
-        let len = cmp::max(start_x, cmp::min(self_w as i32, x + w as i32)) - start_x;
+        let start_x = cmp::max(clip_x1, x);
+        let len = cmp::max(start_x, cmp::min(clip_x2, x + w as i32)) - start_x;
+        if len <= 0 || start_y >= end_y {
+            return;
+        }
 
         let alpha = (color.data >> 24) & 0xFF;
         if alpha > 0 {
@@ -252,7 +1120,7 @@ pub trait Renderer {
                 let data = self.data_mut();
                 for y in start_y..end_y {
                     unsafe {
-                        fast_set32(data.as_mut_ptr().offset((y * self_w as i32 + start_x) as isize) as *mut u32, color.data, len as usize);
+                        fast_set32(data.as_mut_ptr().offset((y as usize * stride as usize + start_x as usize) as isize) as *mut u32, color.data, len as usize);
                     }
                 }
             } else {
@@ -265,7 +1133,122 @@ pub trait Renderer {
         }
     }
 
-    /// Display an image
+    /// Draw a horizontal line of `len` pixels starting at (x, y), filling the span directly
+    fn hline(&mut self, x: i32, y: i32, len: u32, color: Color) {
+        let stride = self.stride() as usize;
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        if y < clip_y1 || y >= clip_y2 {
+            return;
+        }
+
+        let start_x = cmp::max(clip_x1 as i64, x as i64);
+        let end_x = cmp::min(clip_x2 as i64, x as i64 + len as i64);
+        if start_x >= end_x {
+            return;
+        }
+
+        let alpha = (color.data >> 24) & 0xFF;
+        if alpha >= 255 {
+            let data = self.data_mut();
+            let row = &mut data[y as usize * stride + start_x as usize..];
+            unsafe {
+                fast_set32(row.as_mut_ptr() as *mut u32, color.data, (end_x - start_x) as usize);
+            }
+        } else if alpha > 0 {
+            for x in start_x..end_x {
+                self.pixel(x as i32, y, color);
+            }
+        }
+    }
+
+    /// Draw a vertical line of `len` pixels starting at (x, y), filling the span directly
+    fn vline(&mut self, x: i32, y: i32, len: u32, color: Color) {
+        let stride = self.stride() as usize;
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        if x < clip_x1 || x >= clip_x2 {
+            return;
+        }
+
+        let start_y = cmp::max(clip_y1 as i64, y as i64);
+        let end_y = cmp::min(clip_y2 as i64, y as i64 + len as i64);
+        if start_y >= end_y {
+            return;
+        }
+
+        let alpha = (color.data >> 24) & 0xFF;
+        if alpha >= 255 {
+            let data = self.data_mut();
+            for y in start_y..end_y {
+                data[y as usize * stride + x as usize] = color;
+            }
+        } else if alpha > 0 {
+            for y in start_y..end_y {
+                self.pixel(x, y as i32, color);
+            }
+        }
+    }
+
+    /// Draw the outline of a rectangle, covering each border pixel exactly once
+    fn rect_outline(&mut self, x: i32, y: i32, w: u32, h: u32, color: Color) {
+        if w == 0 || h == 0 {
+            return;
+        }
+
+        self.hline(x, y, w, color);
+        if h > 1 {
+            self.hline(x, y + h as i32 - 1, w, color);
+        }
+        if h > 2 {
+            self.vline(x, y + 1, h - 2, color);
+            if w > 1 {
+                self.vline(x + w as i32 - 1, y + 1, h - 2, color);
+            }
+        }
+    }
+
+    /// Fill a rectangle with a checkerboard of `cell` sized squares, starting with `c1` in the top left corner
+    fn checkerboard(&mut self, x: i32, y: i32, w: u32, h: u32, cell: u32, c1: Color, c2: Color) {
+        if cell == 0 {
+            return;
+        }
+
+        let mut row = 0;
+        while row * cell < h {
+            let cell_h = cmp::min(cell, h - row * cell);
+            let mut col = 0;
+            while col * cell < w {
+                let cell_w = cmp::min(cell, w - col * cell);
+                let color = if (row + col) % 2 == 0 { c1 } else { c2 };
+                self.rect(x + (col * cell) as i32, y + (row * cell) as i32, cell_w, cell_h, color);
+                col += 1;
+            }
+            row += 1;
+        }
+    }
+
+    /// Draw a grid of lines `spacing` pixels apart across a rectangle, starting at its top left corner. Crossings are drawn only once so translucent colors blend evenly
+    fn grid(&mut self, x: i32, y: i32, w: u32, h: u32, spacing: u32, color: Color) {
+        if spacing == 0 {
+            return;
+        }
+
+        let mut row = 0;
+        while row < h {
+            self.hline(x, y + row as i32, w, color);
+            // The vertical lines fill the gap down to the next horizontal line
+            let gap = cmp::min(spacing - 1, h - row - 1);
+            if gap > 0 {
+                let mut col = 0;
+                while col < w {
+                    self.vline(x + col as i32, y + row as i32 + 1, gap, color);
+                    col += spacing;
+                }
+            }
+            row += spacing;
+        }
+    }
+
+    /// Display an image, blending it over the existing pixels using its alpha
     // TODO: Improve speed
     fn image(&mut self, start_x: i32, start_y: i32, w: u32, h: u32, data: &[Color]) {
         let mut i = 0;
@@ -280,6 +1263,270 @@ pub trait Renderer {
     }
 
 
+    /// Display the `w` by `h` region at (src_x, src_y) of an image whose rows are `src_stride` pixels long, blending it using its alpha
+    fn image_region(&mut self, dst_x: i32, dst_y: i32, src_x: u32, src_y: u32, w: u32, h: u32, src_stride: u32, data: &[Color]) {
+        // Never read past the end of a source row into the next one
+        let w = cmp::min(w, src_stride.saturating_sub(src_x));
+        for row in 0..h {
+            let start = (src_y as usize + row as usize) * src_stride as usize + src_x as usize;
+            if start >= data.len() {
+                break;
+            }
+            let end = cmp::min(start + w as usize, data.len());
+            for (col, color) in data[start..end].iter().enumerate() {
+                self.pixel(dst_x + col as i32, dst_y + row as i32, *color);
+            }
+        }
+    }
+
+    /// Fill a rectangle by repeating a `tile_w` by `tile_h` image, cutting off partial tiles at the right and bottom edges
+    fn image_tiled(&mut self, x: i32, y: i32, w: u32, h: u32, tile_w: u32, tile_h: u32, data: &[Color]) {
+        if tile_w == 0 || tile_h == 0 {
+            return;
+        }
+
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        let x1 = cmp::max(clip_x1, x);
+        let y1 = cmp::max(clip_y1, y);
+        let x2 = cmp::min(clip_x2, x + w as i32);
+        let y2 = cmp::min(clip_y2, y + h as i32);
+
+        for py in y1..y2 {
+            let row = ((py - y) as u32 % tile_h) * tile_w;
+            for px in x1..x2 {
+                let i = (row + (px - x) as u32 % tile_w) as usize;
+                if i < data.len() {
+                    self.pixel(px, py, data[i]);
+                }
+            }
+        }
+    }
+
+    /// Display a `src_w` by `src_h` image as a nine-patch filling `w` by `h`. The corners, given by the `left`, `right`, `top` and `bottom` margins, keep their size while the edges and center are stretched. Nothing is drawn if the margins are larger than the image
+    fn image_nine_patch(&mut self, x: i32, y: i32, w: u32, h: u32, src_w: u32, src_h: u32, left: u32, right: u32, top: u32, bottom: u32, data: &[Color]) {
+        if left + right > src_w || top + bottom > src_h {
+            return;
+        }
+
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        let x1 = cmp::max(clip_x1, x);
+        let y1 = cmp::max(clip_y1, y);
+        let x2 = cmp::min(clip_x2, x + w as i32);
+        let y2 = cmp::min(clip_y2, y + h as i32);
+        if x1 >= x2 || y1 >= y2 {
+            return;
+        }
+
+        let cols: Vec<Option<u32>> = (x1..x2).map(|px| {
+            nine_patch_offset((px - x) as u32, w, src_w, left, right)
+        }).collect();
+
+        for py in y1..y2 {
+            let row = match nine_patch_offset((py - y) as u32, h, src_h, top, bottom) {
+                Some(row) => row * src_w,
+                None => continue
+            };
+            for (px, col) in (x1..x2).zip(cols.iter()) {
+                if let Some(col) = *col {
+                    let i = (row + col) as usize;
+                    if i < data.len() {
+                        self.pixel(px, py, data[i]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Display a canvas, blending it over the existing pixels using its alpha
+    fn image_canvas(&mut self, x: i32, y: i32, canvas: &Canvas) {
+        self.image(x, y, canvas.width(), canvas.height(), canvas.data());
+    }
+
+    /// Display an image, mirrored horizontally if `flip_h` and vertically if `flip_v`
+    fn image_flipped(&mut self, start_x: i32, start_y: i32, w: u32, h: u32, data: &[Color], flip_h: bool, flip_v: bool) {
+        for row in 0..h {
+            let src_row = if flip_v { h - 1 - row } else { row };
+            for col in 0..w {
+                let src_col = if flip_h { w - 1 - col } else { col };
+                let i = (src_row * w + src_col) as usize;
+                if i < data.len() {
+                    self.pixel(start_x + col as i32, start_y + row as i32, data[i]);
+                }
+            }
+        }
+    }
+
+    /// Display an image rotated by `angle` radians around its center, which is placed at (cx, cy). Positive angles turn clockwise on screen
+    fn image_rotated(&mut self, cx: i32, cy: i32, w: u32, h: u32, angle: f32, data: &[Color]) {
+        // Snap values that are almost whole so quarter turns map pixels exactly
+        let snap = |v: f32| if (v - v.round()).abs() < 1e-6 { v.round() } else { v };
+        let (sin, cos) = angle.sin_cos();
+        let (sin, cos) = (snap(sin), snap(cos));
+        let half_w = w as f32 / 2.0;
+        let half_h = h as f32 / 2.0;
+        // Half the size of the box around the rotated image
+        let extent_x = (half_w * cos.abs() + half_h * sin.abs()).ceil() as i32;
+        let extent_y = (half_w * sin.abs() + half_h * cos.abs()).ceil() as i32;
+
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        let x1 = cmp::max(clip_x1, cx - extent_x);
+        let y1 = cmp::max(clip_y1, cy - extent_y);
+        let x2 = cmp::min(clip_x2, cx + extent_x);
+        let y2 = cmp::min(clip_y2, cy + extent_y);
+
+        // Map the center of each destination pixel back into the source, taking the nearest pixel
+        for y in y1..y2 {
+            let dy = (y - cy) as f32 + 0.5;
+            for x in x1..x2 {
+                let dx = (x - cx) as f32 + 0.5;
+                let src_x = (dx * cos + dy * sin + half_w).floor();
+                let src_y = (dy * cos - dx * sin + half_h).floor();
+                if src_x >= 0.0 && src_y >= 0.0 && src_x < w as f32 && src_y < h as f32 {
+                    let i = (src_y as u32 * w + src_x as u32) as usize;
+                    if i < data.len() {
+                        self.pixel(x, y, data[i]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Display an image, copying rows directly and ignoring alpha
+    fn image_fast(&mut self, start_x: i32, start_y: i32, w: u32, h: u32, data: &[Color]) {
+        let stride = self.stride() as i32;
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+
+        let x1 = cmp::max(clip_x1, start_x);
+        let x2 = cmp::min(clip_x2, start_x + w as i32);
+        let y1 = cmp::max(clip_y1, start_y);
+        let y2 = cmp::min(clip_y2, start_y + h as i32);
+        if x1 >= x2 {
+            return;
+        }
+
+        let dst = self.data_mut();
+        for y in y1..y2 {
+            let src_start = ((y - start_y) * w as i32 + x1 - start_x) as usize;
+            let src_end = cmp::min(src_start + (x2 - x1) as usize, data.len());
+            if src_start >= src_end {
+                break;
+            }
+
+            let dst_start = (y * stride + x1) as usize;
+            dst[dst_start..dst_start + src_end - src_start].copy_from_slice(&data[src_start..src_end]);
+        }
+    }
+
+    /// Move a `w` by `h` block of pixels from (src_x, src_y) to (dst_x, dst_y), as when scrolling. The source and destination may overlap. Parts of the source outside the buffer are not copied, and the destination is clipped
+    fn copy_region(&mut self, src_x: i32, src_y: i32, w: u32, h: u32, dst_x: i32, dst_y: i32) {
+        let stride = self.stride() as i32;
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+        let off_x = dst_x - src_x;
+        let off_y = dst_y - src_y;
+
+        // The part of the source that is inside the buffer and lands inside the clip, in source coordinates
+        let x1 = cmp::max(cmp::max(0, src_x), clip_x1 - off_x);
+        let y1 = cmp::max(cmp::max(0, src_y), clip_y1 - off_y);
+        let x2 = cmp::min(cmp::min(self.width() as i32, src_x + w as i32), clip_x2 - off_x);
+        let y2 = cmp::min(cmp::min(self.height() as i32, src_y + h as i32), clip_y2 - off_y);
+        if x1 >= x2 || y1 >= y2 || (off_x == 0 && off_y == 0) {
+            return;
+        }
+
+        let len = (x2 - x1) as usize;
+        let mut row = vec![Color::rgba(0, 0, 0, 0); len];
+        let data = self.data_mut();
+        // Copy rows away from the direction of movement so none are overwritten before they are read
+        let rows: Vec<i32> = if off_y > 0 { (y1..y2).rev().collect() } else { (y1..y2).collect() };
+        for y in rows {
+            let src = (y * stride + x1) as usize;
+            let dst = ((y + off_y) * stride + x1 + off_x) as usize;
+            row.copy_from_slice(&data[src..src + len]);
+            data[dst..dst + len].copy_from_slice(&row);
+        }
+    }
+
+    /// Blur a region in place with a box blur of the given radius, done as a horizontal then a vertical pass
+    fn blur(&mut self, x: i32, y: i32, w: u32, h: u32, radius: u32) {
+        let stride = self.stride() as usize;
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+
+        let x1 = cmp::max(clip_x1, x);
+        let y1 = cmp::max(clip_y1, y);
+        let x2 = cmp::min(clip_x2, x + w as i32);
+        let y2 = cmp::min(clip_y2, y + h as i32);
+        if radius == 0 || x1 >= x2 || y1 >= y2 {
+            return;
+        }
+
+        let region_w = (x2 - x1) as usize;
+        let region_h = (y2 - y1) as usize;
+        let start = y1 as usize * stride + x1 as usize;
+        let mut line = Vec::with_capacity(cmp::max(region_w, region_h));
+        // Past the edges the pixels repeat, so a radius beyond the region changes nothing
+        let radius = cmp::min(radius as usize, cmp::max(region_w, region_h));
+
+        let data = self.data_mut();
+        for row in 0..region_h {
+            blur_line(&mut data[start + row * stride..], region_w, 1, radius, &mut line);
+        }
+        for col in 0..region_w {
+            blur_line(&mut data[start + col..], region_h, stride, radius, &mut line);
+        }
+    }
+
+    /// Adjust the colors of a region in place
+    fn adjust_region(&mut self, x: i32, y: i32, w: u32, h: u32, op: Adjust) {
+        let stride = self.stride() as usize;
+        let (clip_x1, clip_y1, clip_x2, clip_y2) = self.clip_bounds();
+
+        let x1 = cmp::max(clip_x1, x);
+        let y1 = cmp::max(clip_y1, y);
+        let x2 = cmp::min(clip_x2, x + w as i32);
+        let y2 = cmp::min(clip_y2, y + h as i32);
+        if x1 >= x2 || y1 >= y2 {
+            return;
+        }
+
+        let clamp = |value: i32| cmp::max(0, cmp::min(255, value)) as u8;
+        let data = self.data_mut();
+        for row in y1..y2 {
+            let start = row as usize * stride;
+            for color in data[start + x1 as usize..start + x2 as usize].iter_mut() {
+                let (r, g, b, a) = (color.r() as i32, color.g() as i32, color.b() as i32, color.a());
+                *color = match op {
+                    Adjust::Brightness(amount) => {
+                        let amount = amount as i32;
+                        Color::rgba(clamp(r + amount), clamp(g + amount), clamp(b + amount), a)
+                    },
+                    Adjust::Contrast(factor) => {
+                        let scale = |value: i32| clamp(((value - 128) as f32 * factor) as i32 + 128);
+                        Color::rgba(scale(r), scale(g), scale(b), a)
+                    },
+                    Adjust::Invert => Color::rgba(255 - r as u8, 255 - g as u8, 255 - b as u8, a),
+                    Adjust::Grayscale => {
+                        let luma = clamp((r * 299 + g * 587 + b * 114) / 1000);
+                        Color::rgba(luma, luma, luma, a)
+                    }
+                };
+            }
+        }
+    }
+
+    /// Fill a rectangle with a gradient from `start_color` to `end_color`, top to bottom if `vertical` and left to right otherwise
+    fn gradient(&mut self, x: i32, y: i32, w: u32, h: u32, start_color: Color, end_color: Color, vertical: bool) {
+        let steps = if vertical { h } else { w };
+        for i in 0..steps {
+            let scale = if steps > 1 { i as f64 / (steps - 1) as f64 } else { 0.0 };
+            let color = Color::interpolate(start_color, end_color, scale);
+            if vertical {
+                self.rect(x, y + i as i32, w, 1, color);
+            } else {
+                self.rect(x + i as i32, y, 1, h, color);
+            }
+        }
+    }
+
     /// Draw a linear gradient in a rectangular region
     fn linear_gradient(&mut self, rect_x: i32, rect_y: i32, rect_width: u32, rect_height:u32, start_x: i32, start_y: i32, end_x: i32, end_y: i32, start_color: Color, end_color: Color) {
         if (start_x == end_x) && (start_y == end_y) {
@@ -329,8 +1576,8 @@ pub trait Renderer {
     fn rounded_rect(&mut self, x: i32, y: i32, w: u32, h: u32, radius: u32, filled: bool, color: Color) {
         let w = w as i32;
         let h = h as i32;
-        let r = radius as i32;
-
+        // Keep the corners from overlapping, so the edge lengths below never go negative
+        let r = cmp::max(0, cmp::min(radius as i32, (cmp::min(w, h) - 2) / 2));
 
         if filled {
             //Draw inside corners
@@ -340,9 +1587,9 @@ pub trait Renderer {
             self.arc(x + w - 1 - r, y + h - 1 - r, -r, 1 << 1 | 1 << 3, color);
 
             // Draw inside rectangles
-            self.rect(x + r, y, (w - 1 - r * 2) as u32, r as u32 + 1, color);
-            self.rect(x + r, y + h - 1 - r, (w - 1 - r * 2) as u32, r as u32 + 1, color);
-            self.rect(x, y + r + 1, w as u32, (h - 2 - r * 2) as u32, color);
+            self.rect(x + r, y, cmp::max(0, w - 1 - r * 2) as u32, r as u32 + 1, color);
+            self.rect(x + r, y + h - 1 - r, cmp::max(0, w - 1 - r * 2) as u32, r as u32 + 1, color);
+            self.rect(x, y + r + 1, w as u32, cmp::max(0, h - 2 - r * 2) as u32, color);
         } else {
             //Draw outside corners
             self.arc(x + r, y + r, r, 1 << 4 | 1 << 6, color);
@@ -351,10 +1598,137 @@ pub trait Renderer {
             self.arc(x + w - 1 - r, y + h - 1 - r, r, 1 << 1 | 1 << 3, color);
 
             // Draw outside rectangles
-            self.rect(x + r + 1, y, (w - 2 - r * 2) as u32, 1, color);
-            self.rect(x + r + 1, y + h - 1, (w - 2 - r * 2) as u32, 1, color);
-            self.rect(x, y + r + 1, 1, (h - 2 - r * 2) as u32, color);
-            self.rect(x + w - 1, y + r + 1, 1, (h - 2 - r * 2) as u32, color);
+            self.rect(x + r + 1, y, cmp::max(0, w - 2 - r * 2) as u32, 1, color);
+            self.rect(x + r + 1, y + h - 1, cmp::max(0, w - 2 - r * 2) as u32, 1, color);
+            self.rect(x, y + r + 1, 1, cmp::max(0, h - 2 - r * 2) as u32, color);
+            self.rect(x + w - 1, y + r + 1, 1, cmp::max(0, h - 2 - r * 2) as u32, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::i32;
+
+    use canvas::Canvas;
+    use color::Color;
+    use super::{bezier_steps, clip_line, Renderer, MAX_CURVE_STEPS};
+
+    const BOUNDS: (i32, i32, i32, i32) = (0, 0, 9, 9);
+
+    fn data(color: Color) -> u32 {
+        color.data
+    }
+
+    #[test]
+    fn clip_line_inside() {
+        assert_eq!(clip_line(BOUNDS, 1, 2, 8, 7), Some((1, 2, 8, 7)));
+        assert_eq!(clip_line(BOUNDS, 0, 0, 9, 9), Some((0, 0, 9, 9)));
+    }
+
+    #[test]
+    fn clip_line_each_side() {
+        assert_eq!(clip_line(BOUNDS, -5, 4, 5, 4), Some((0, 4, 5, 4)));
+        assert_eq!(clip_line(BOUNDS, 5, 4, 15, 4), Some((5, 4, 9, 4)));
+        assert_eq!(clip_line(BOUNDS, 4, -5, 4, 5), Some((4, 0, 4, 5)));
+        assert_eq!(clip_line(BOUNDS, 4, 5, 4, 15), Some((4, 5, 4, 9)));
+        assert_eq!(clip_line(BOUNDS, -10, -10, 20, 20), Some((0, 0, 9, 9)));
+        assert_eq!(clip_line(BOUNDS, -2, 11, 11, -2), Some((0, 9, 9, 0)));
+    }
+
+    #[test]
+    fn clip_line_outside() {
+        assert_eq!(clip_line(BOUNDS, -5, 0, -1, 9), None);
+        assert_eq!(clip_line(BOUNDS, 10, 0, 15, 9), None);
+        assert_eq!(clip_line(BOUNDS, 0, -5, 9, -1), None);
+        assert_eq!(clip_line(BOUNDS, 0, 10, 9, 15), None);
+        // Crosses two outside regions without touching the rectangle
+        assert_eq!(clip_line(BOUNDS, -5, 4, 4, -5), None);
+        assert_eq!(clip_line((5, 5, 4, 4), 0, 0, 9, 9), None);
+    }
+
+    #[test]
+    fn clip_line_extremes() {
+        assert_eq!(clip_line(BOUNDS, 4, i32::MIN, 4, i32::MAX), Some((4, 0, 4, 9)));
+        assert_eq!(clip_line(BOUNDS, i32::MIN, 4, i32::MAX, 4), Some((0, 4, 9, 4)));
+        assert_eq!(clip_line(BOUNDS, i32::MIN, i32::MIN, i32::MAX, i32::MAX), Some((0, 0, 9, 9)));
+        assert_eq!(clip_line(BOUNDS, i32::MAX, 9 - i32::MAX, 9 - i32::MAX, i32::MAX), Some((9, 0, 0, 9)));
+        assert_eq!(clip_line((0, 1000, 9, 1009), i32::MIN, i32::MIN, i32::MAX, i32::MAX), None);
+        assert_eq!(clip_line((1000, 1000, 1009, 1009), i32::MIN, i32::MIN, i32::MAX, i32::MAX), Some((1000, 1000, 1009, 1009)));
+        assert_eq!(clip_line(BOUNDS, i32::MIN, i32::MIN, i32::MIN, i32::MAX), None);
+    }
+
+    #[test]
+    fn bezier_steps_bounded() {
+        assert_eq!(bezier_steps(&[[0, 0], [0, 0], [0, 0]]), 1);
+        assert_eq!(bezier_steps(&[[0, 0], [10, 0], [10, 10]]), 10);
+        assert_eq!(bezier_steps(&[[0, 0], [i32::MAX, i32::MIN], [i32::MIN, i32::MAX], [0, 0]]), MAX_CURVE_STEPS);
+    }
+
+    #[test]
+    fn polygon_fill_far_off() {
+        let mut canvas = Canvas::new(20, 20);
+        let color = Color::rgb(255, 0, 0);
+        canvas.polygon_fill(&[[-2000000000, 0], [2000000000, 5], [0, 10]], color);
+        assert_eq!(data(canvas.get_pixel(0, 3)), data(color));
+        assert_eq!(data(canvas.get_pixel(0, 15)), 0);
+    }
+
+    #[test]
+    fn triangle_far_off() {
+        let mut canvas = Canvas::new(20, 20);
+        let color = Color::rgb(255, 0, 0);
+        canvas.triangle(-2000000000, 0, 2000000000, 5, 0, 10, color);
+        assert_eq!(data(canvas.get_pixel(0, 3)), data(color));
+        assert_eq!(data(canvas.get_pixel(0, 15)), 0);
+    }
+
+    #[test]
+    fn blur_huge_radius() {
+        let mut canvas = Canvas::new(20, 20);
+        let color = Color::rgb(255, 255, 255);
+        canvas.set(color);
+        canvas.blur(0, 0, 20, 20, 100000000);
+        assert_eq!(data(canvas.get_pixel(10, 10)), data(color));
+    }
+
+    #[test]
+    fn thick_line_far_off() {
+        let mut canvas = Canvas::new(20, 20);
+        let color = Color::rgb(255, 0, 0);
+        canvas.thick_line(5, -200000000, 5, 200000000, 3, color);
+        assert_eq!(data(canvas.get_pixel(5, 0)), data(color));
+        assert_eq!(data(canvas.get_pixel(5, 19)), data(color));
+        assert_eq!(data(canvas.get_pixel(8, 10)), 0);
+    }
+
+    #[test]
+    fn rect_outside_clip() {
+        let mut canvas = Canvas::new(20, 20);
+        let color = Color::rgb(255, 0, 0);
+        canvas.rect(25, 0, 10, 20, color);
+        canvas.rect(0, 25, 20, 10, color);
+        canvas.rect(19, 19, 5, 5, color);
+        assert_eq!(data(canvas.get_pixel(19, 19)), data(color));
+        assert_eq!(data(canvas.get_pixel(18, 18)), 0);
+    }
+
+    #[test]
+    fn hline_vline_clipped() {
+        let mut canvas = Canvas::new(20, 20);
+        let color = Color::rgb(255, 0, 0);
+        canvas.set_clip(Some((5, 5, 10, 10)));
+        canvas.hline(-100, 7, 1000, color);
+        canvas.vline(8, -100, 1000, color);
+        canvas.hline(0, 2, 20, color);
+        canvas.vline(17, 0, 20, color);
+        canvas.set_clip(None);
+        for i in 0..20 {
+            let inside = i >= 5 && i < 15;
+            assert_eq!(data(canvas.get_pixel(i, 7)) == data(color), inside);
+            assert_eq!(data(canvas.get_pixel(8, i)) == data(color), inside);
+            assert_eq!(data(canvas.get_pixel(i, 2)), 0);
+            assert_eq!(data(canvas.get_pixel(17, i)), 0);
         }
     }
 }
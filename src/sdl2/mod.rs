@@ -1,12 +1,18 @@
 extern crate sdl2;
 
 use std::ptr;
+use std::os::raw::{c_char, c_void};
 use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
 
 pub use self::display::*;
 pub use self::window::*;
 
+#[cfg(feature = "ttf")]
+pub use self::ttf::*;
+
 mod display;
+#[cfg(feature = "ttf")]
+mod ttf;
 mod window;
 
 pub static SDL_USAGES: AtomicUsize = ATOMIC_USIZE_INIT;
@@ -14,6 +20,11 @@ pub static SDL_USAGES: AtomicUsize = ATOMIC_USIZE_INIT;
 pub static mut SDL_CTX: *mut sdl2::Sdl = ptr::null_mut();
 /// Video Context
 pub static mut VIDEO_CTX: *mut sdl2::VideoSubsystem = ptr::null_mut();
+/// Game Controller Context, which may be null if controllers are unavailable
+pub static mut CONTROLLER_CTX: *mut sdl2::GameControllerSubsystem = ptr::null_mut();
+/// TrueType Font Context, created when the first font is loaded
+#[cfg(feature = "ttf")]
+pub static mut TTF_CTX: *mut sdl2::ttf::Sdl2TtfContext = ptr::null_mut();
 /// Event Pump
 pub static mut EVENT_PUMP: *mut sdl2::EventPump = ptr::null_mut();
 
@@ -23,6 +34,46 @@ unsafe fn init() {
     if SDL_USAGES.fetch_add(1, Ordering::Relaxed) == 0 {
         SDL_CTX = Box::into_raw(Box::new(sdl2::init().unwrap()));
         VIDEO_CTX = Box::into_raw(Box::new((&mut *SDL_CTX).video().unwrap()));
+        (&*VIDEO_CTX).text_input().start();
+        if let Ok(controller_ctx) = (&mut *SDL_CTX).game_controller() {
+            CONTROLLER_CTX = Box::into_raw(Box::new(controller_ctx));
+        }
         EVENT_PUMP = Box::into_raw(Box::new((&mut *SDL_CTX).event_pump().unwrap()));
     }
 }
+
+/// Look up an SDL function by its null terminated name, returning null if this SDL does not have it. Used for functions added after the oldest supported SDL, so the crate still links against it
+#[cfg(unix)]
+unsafe fn function(name: &[u8]) -> *mut c_void {
+    extern "C" {
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+    const RTLD_DEFAULT: isize = -2;
+    #[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "freebsd")))]
+    const RTLD_DEFAULT: isize = 0;
+
+    dlsym(RTLD_DEFAULT as *mut c_void, name.as_ptr() as *const c_char)
+}
+
+/// Look up an SDL function by its null terminated name, returning null if this SDL does not have it. Used for functions added after the oldest supported SDL, so the crate still links against it
+#[cfg(windows)]
+unsafe fn function(name: &[u8]) -> *mut c_void {
+    extern "system" {
+        fn GetModuleHandleA(module: *const c_char) -> *mut c_void;
+        fn GetProcAddress(module: *mut c_void, name: *const c_char) -> *mut c_void;
+    }
+
+    let module = GetModuleHandleA(b"SDL2.dll\0".as_ptr() as *const c_char);
+    if module.is_null() {
+        return ptr::null_mut();
+    }
+    GetProcAddress(module, name.as_ptr() as *const c_char)
+}
+
+/// Look up an SDL function by its null terminated name. There is no way to do so on this platform, so this is always null
+#[cfg(not(any(unix, windows)))]
+unsafe fn function(_name: &[u8]) -> *mut c_void {
+    ptr::null_mut()
+}
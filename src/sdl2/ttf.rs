@@ -0,0 +1,77 @@
+extern crate sdl2;
+
+use std::{mem, slice};
+
+use color::Color;
+use renderer::Renderer;
+use super::{init, TTF_CTX, Window};
+
+/// A TrueType font, loaded with SDL_ttf
+pub struct Font {
+    /// The inner font
+    inner: sdl2::ttf::Font<'static, 'static>,
+}
+
+impl Font {
+    /// Load a font from a file at a point size
+    pub fn from_file(path: &str, size: u16) -> Option<Self> {
+        unsafe {
+            init();
+            if TTF_CTX.is_null() {
+                match sdl2::ttf::init() {
+                    Ok(ttf_ctx) => TTF_CTX = Box::into_raw(Box::new(ttf_ctx)),
+                    Err(_) => return None
+                }
+            }
+        }
+
+        match unsafe { & *TTF_CTX }.load_font(path, size) {
+            Ok(font) => Some(Font {
+                inner: font,
+            }),
+            Err(_) => None
+        }
+    }
+
+    /// Get the size in pixels that `Window::string_ttf` would use to draw some text
+    pub fn size_of(&self, text: &str) -> (u32, u32) {
+        self.inner.size_of(text).unwrap_or((0, 0))
+    }
+}
+
+impl Window {
+    /// Draw a string with a TrueType font, anti-aliased and blended using the alpha of `color`. (x, y) is the top left of the text
+    pub fn string_ttf(&mut self, x: i32, y: i32, text: &str, color: Color, font: &Font) {
+        if text.is_empty() {
+            return;
+        }
+
+        let mut rendered = match font.inner.render(text).blended(sdl2::pixels::Color::RGBA(color.r(), color.g(), color.b(), color.a())) {
+            Ok(rendered) => rendered,
+            Err(_) => return
+        };
+
+        // Copy into a known format without blending, so the alpha of each pixel is kept
+        let (w, h) = rendered.size();
+        let mut surface = match sdl2::surface::Surface::new(w, h, sdl2::pixels::PixelFormatEnum::ARGB8888) {
+            Ok(surface) => surface,
+            Err(_) => return
+        };
+        let _ = rendered.set_blend_mode(sdl2::render::BlendMode::None);
+        if rendered.blit(None, &mut surface, None).is_err() {
+            return;
+        }
+
+        let stride = surface.pitch() as usize / mem::size_of::<Color>();
+        if let Some(bytes) = surface.without_lock() {
+            let pixels = unsafe { slice::from_raw_parts(bytes.as_ptr() as *const Color, bytes.len() / mem::size_of::<Color>()) };
+            for row in 0..h as usize {
+                for col in 0..w as usize {
+                    if let Some(&pixel) = pixels.get(row * stride + col) {
+                        self.pixel(x + col as i32, y + row as i32, pixel);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,12 +1,95 @@
 extern crate sdl2;
 
-use std::{mem, slice};
+use std::{cmp, mem, slice, thread};
+use std::collections::VecDeque;
+use std::os::raw::{c_int, c_void};
+use std::time::{Duration, Instant};
 
+use FONT;
+use bmp::BmpFile;
 use color::Color;
 use event::*;
-use super::{init, SDL_CTX, VIDEO_CTX, EVENT_PUMP};
+use super::{function, init, SDL_CTX, VIDEO_CTX, CONTROLLER_CTX, EVENT_PUMP};
 use renderer::Renderer;
 
+// Not yet wrapped by the sdl2 crate. Requires SDL 2.0.5, and only has an effect on X11
+const SDL_WINDOW_ALWAYS_ON_TOP: u32 = 0x00008000;
+
+// Used to check whether the renderer was created with vsync
+const SDL_RENDERER_PRESENTVSYNC: u32 = 0x00000004;
+
+// Used to fall back to a software renderer when recreating one
+const SDL_RENDERER_SOFTWARE: u32 = 0x00000001;
+
+// Used to recreate the renderer of a window without giving up the window, which the sdl2 crate's builder does on failure
+extern "C" {
+    fn SDL_CreateRenderer(window: *mut c_void, index: c_int, flags: u32) -> *mut c_void;
+}
+
+// Not yet wrapped by the sdl2 crate. Requires SDL 2.0.5, so it is looked up when called
+type SetWindowOpacity = extern "C" fn(window: *mut c_void, opacity: f32) -> c_int;
+
+// Not yet wrapped by the sdl2 crate. Requires SDL 2.0.16, so it is looked up when called
+type SetWindowAlwaysOnTop = extern "C" fn(window: *mut c_void, on_top: c_int);
+
+/// How far in pixels the mouse can move between the presses of a double click
+const DOUBLE_CLICK_DISTANCE: i32 = 4;
+
+/// A window creation flag
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WindowFlag {
+    /// Do not wait for events
+    Async,
+    /// Do not draw a border or title bar
+    Borderless,
+    /// Allow the user to resize the window
+    Resizable,
+    /// Keep the window above other windows
+    Front,
+    /// Present with a hardware accelerated renderer. Drawing still happens in memory, and every sync uploads the whole buffer to a texture, so this pays off when presenting large windows often
+    Accelerated,
+    /// Wait for the display to refresh when presenting, which prevents tearing and limits the frame rate. The software renderer may ignore this, so it is best combined with `Accelerated`
+    Vsync,
+}
+
+/// A fullscreen mode
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FullscreenMode {
+    /// A normal window
+    Off,
+    /// A borderless window covering the desktop, keeping the display mode
+    Desktop,
+    /// Exclusive fullscreen, changing the display mode to the window size
+    Exclusive,
+}
+
+/// A system mouse cursor shape
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CursorStyle {
+    /// The default arrow
+    Arrow,
+    /// A pointing hand, for links and buttons
+    Hand,
+    /// A text cursor
+    IBeam,
+    /// A crosshair
+    Crosshair,
+    /// A busy indicator
+    Wait,
+    /// A double arrow pointing northwest and southeast
+    SizeNWSE,
+    /// A double arrow pointing northeast and southwest
+    SizeNESW,
+    /// A double arrow pointing west and east
+    SizeWE,
+    /// A double arrow pointing north and south
+    SizeNS,
+    /// A four pointed arrow
+    SizeAll,
+    /// A slashed circle, for actions that are not allowed
+    No,
+}
+
 /// A window
 #[allow(dead_code)]
 pub struct Window {
@@ -22,6 +105,28 @@ pub struct Window {
     t: String,
     /// True if the window should not wait for events
     async: bool,
+    /// True if the window has been asked to close
+    closing: bool,
+    /// The time, position and button of the last press that could start a double click
+    last_click: Option<(Instant, i32, i32, u8)>,
+    /// The longest time between the presses of a double click
+    double_click_ms: u32,
+    /// The shortest time between presents, if the frame rate is limited
+    frame_interval: Option<Duration>,
+    /// The time of the last present
+    last_frame: Option<Instant>,
+    /// The clipping rectangle
+    clip: Option<(i32, i32, u32, u32)>,
+    /// The custom font and its glyph height, if one is set
+    font: Option<(Vec<u8>, usize)>,
+    /// The game controllers that are open, which is needed to receive their events
+    controllers: Vec<sdl2::controller::GameController>,
+    /// The paths of dropped files that have not been taken yet
+    drop_paths: VecDeque<String>,
+    /// The active system cursor, which must be kept alive while it is shown
+    cursor: Option<sdl2::mouse::Cursor>,
+    /// The memory buffer and streaming texture used in place of the window surface by accelerated windows
+    buffer: Option<(sdl2::surface::Surface<'static>, sdl2::render::Texture)>,
     /// The inner renderer
     inner: sdl2::render::Renderer<'static>,
 }
@@ -39,35 +144,59 @@ impl Renderer for Window {
 
     /// Access pixel buffer
     fn data(&self) -> &[Color] {
-        let window = self.inner.window().unwrap();
-        let surface = window.surface(unsafe { & *EVENT_PUMP }).unwrap();
-        let bytes = surface.without_lock().unwrap();
+        let bytes = self.surface().without_lock().unwrap();
         unsafe { slice::from_raw_parts(bytes.as_ptr() as *const Color, bytes.len()/mem::size_of::<Color>()) }
     }
 
     /// Access pixel buffer mutably
     fn data_mut(&mut self) -> &mut [Color] {
-        let window = self.inner.window_mut().unwrap();
-        let surface = window.surface_mut(unsafe { & *EVENT_PUMP }).unwrap();
-        let bytes = surface.without_lock_mut().unwrap();
+        let bytes = self.surface_mut().without_lock_mut().unwrap();
         unsafe { slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut Color, bytes.len()/mem::size_of::<Color>()) }
     }
 
+    /// Get the row stride of the window surface in pixels
+    fn stride(&self) -> u32 {
+        self.surface().pitch() / mem::size_of::<Color>() as u32
+    }
+
     /// Flip the window buffer
     fn sync(&mut self) -> bool {
+        if let Some((ref surface, ref mut texture)) = self.buffer {
+            let pitch = surface.pitch() as usize;
+            if texture.update(None, surface.without_lock().unwrap(), pitch).is_err() {
+                return false;
+            }
+            if self.inner.copy(texture, None, None).is_err() {
+                return false;
+            }
+        }
+        self.wait_frame();
         self.inner.present();
         true
     }
+
+    /// Get the clipping rectangle
+    fn clip(&self) -> Option<(i32, i32, u32, u32)> {
+        self.clip
+    }
+
+    /// Get the custom font if one is set, or the built-in font
+    fn font(&self) -> (&[u8], usize) {
+        match self.font {
+            Some((ref data, height)) => (data, height),
+            None => (FONT, 16)
+        }
+    }
 }
 
 impl Window {
     /// Create a new window
     pub fn new(x: i32, y: i32, w: u32, h: u32, title: &str) -> Option<Self> {
-        Window::new_flags(x, y, w, h, title, false)
+        Window::new_flags(x, y, w, h, title, &[])
     }
 
     /// Create a new window with flags
-    pub fn new_flags(x: i32, y: i32, w: u32, h: u32, title: &str, async: bool) -> Option<Self> {
+    pub fn new_flags(x: i32, y: i32, w: u32, h: u32, title: &str, flags: &[WindowFlag]) -> Option<Self> {
         //Insure that init has been called
         unsafe { init() };
 
@@ -77,24 +206,156 @@ impl Window {
             builder.position(x, y);
         }
 
-        if title.is_empty() {
+        if title.is_empty() || flags.contains(&WindowFlag::Borderless) {
             builder.borderless();
         }
 
-        match builder.build() {
-            Ok(window) => Some(Window {
-                x: x,
-                y: y,
+        if flags.contains(&WindowFlag::Resizable) {
+            builder.resizable();
+        }
+
+        if flags.contains(&WindowFlag::Front) {
+            let window_flags = builder.window_flags();
+            builder.set_window_flags(window_flags | SDL_WINDOW_ALWAYS_ON_TOP);
+        }
+
+        let window = match builder.build() {
+            Ok(window) => window,
+            Err(_) => return None
+        };
+
+        let accelerated = flags.contains(&WindowFlag::Accelerated);
+        let mut renderer_builder = if accelerated {
+            window.renderer().accelerated()
+        } else {
+            window.renderer().software()
+        };
+        if flags.contains(&WindowFlag::Vsync) {
+            renderer_builder = renderer_builder.present_vsync();
+        }
+        let inner = match renderer_builder.build() {
+            Ok(inner) => inner,
+            Err(_) => return None
+        };
+
+        let buffer = if accelerated {
+            match Window::create_buffer(&inner, w, h) {
+                Some(buffer) => Some(buffer),
+                None => return None
+            }
+        } else {
+            None
+        };
+
+        Some(Window {
+            x: x,
+            y: y,
+            w: w,
+            h: h,
+            t: title.to_string(),
+            async: flags.contains(&WindowFlag::Async),
+            closing: false,
+            last_click: None,
+            double_click_ms: 500,
+            frame_interval: None,
+            last_frame: None,
+            clip: None,
+            font: None,
+            controllers: Vec::new(),
+            drop_paths: VecDeque::new(),
+            cursor: None,
+            buffer: buffer,
+            inner: inner,
+        })
+    }
+
+    /// Create the memory buffer and matching streaming texture for an accelerated window
+    fn create_buffer(inner: &sdl2::render::Renderer<'static>, w: u32, h: u32) -> Option<(sdl2::surface::Surface<'static>, sdl2::render::Texture)> {
+        let surface = match sdl2::surface::Surface::new(w, h, sdl2::pixels::PixelFormatEnum::ARGB8888) {
+            Ok(surface) => surface,
+            Err(_) => return None
+        };
+        match inner.create_texture_streaming(sdl2::pixels::PixelFormatEnum::ARGB8888, w, h) {
+            Ok(texture) => Some((surface, texture)),
+            Err(_) => None
+        }
+    }
+
+    /// Create a renderer for a window from SDL renderer flags, along with a streaming texture if `texture` is true. Gives the window back if either fails
+    fn create_renderer(window: sdl2::video::Window, flags: u32, texture: bool, w: u32, h: u32) -> Result<(sdl2::render::Renderer<'static>, Option<sdl2::render::Texture>), sdl2::video::Window> {
+        let raw = unsafe { SDL_CreateRenderer(window.raw() as *mut c_void, -1, flags) };
+        if raw.is_null() {
+            return Err(window);
+        }
+        let inner = unsafe { sdl2::render::Renderer::from_ll(raw as *mut _, sdl2::render::RendererParent::Window(window)) };
+
+        if ! texture {
+            return Ok((inner, None));
+        }
+        match inner.create_texture_streaming(sdl2::pixels::PixelFormatEnum::ARGB8888, w, h) {
+            Ok(texture) => Ok((inner, Some(texture))),
+            Err(_) => Err(inner.into_window().unwrap())
+        }
+    }
+
+    /// Create a new window that renders into memory only, without being shown
+    pub fn new_offscreen(w: u32, h: u32) -> Option<Self> {
+        let surface = match sdl2::surface::Surface::new(w, h, sdl2::pixels::PixelFormatEnum::ARGB8888) {
+            Ok(surface) => surface,
+            Err(_) => return None
+        };
+
+        match sdl2::render::Renderer::from_surface(surface) {
+            Ok(renderer) => Some(Window {
+                x: 0,
+                y: 0,
                 w: w,
                 h: h,
-                t: title.to_string(),
-                async: async,
-                inner: window.renderer().software().build().unwrap(),
+                t: String::new(),
+                async: true,
+                closing: false,
+                last_click: None,
+                double_click_ms: 500,
+                frame_interval: None,
+                last_frame: None,
+                clip: None,
+                font: None,
+                controllers: Vec::new(),
+                drop_paths: VecDeque::new(),
+                cursor: None,
+                buffer: None,
+                inner: renderer,
             }),
             Err(_) => None
         }
     }
 
+    /// Get the surface that is drawn to, either the window's or an offscreen one
+    fn surface(&self) -> &sdl2::surface::SurfaceRef {
+        if let Some((ref surface, _)) = self.buffer {
+            return surface;
+        }
+
+        match self.inner.window() {
+            Some(window) => window.surface(unsafe { & *EVENT_PUMP }).unwrap(),
+            None => self.inner.surface().unwrap()
+        }
+    }
+
+    /// Get the surface that is drawn to mutably
+    fn surface_mut(&mut self) -> &mut sdl2::surface::SurfaceRef {
+        if let Some((ref mut surface, _)) = self.buffer {
+            return surface;
+        }
+
+        if self.inner.window().is_some() {
+            self.inner.window_mut().unwrap().surface_mut(unsafe { & *EVENT_PUMP }).unwrap()
+        } else {
+            self.inner.surface_mut().unwrap()
+        }
+    }
+
+    /// Read the position, size and title back from SDL. This is done for every window event, so the position is current when a `MoveEvent` arrives
     pub fn sync_path(&mut self) {
         if let Some(window) = self.inner.window() {
             let pos = window.position();
@@ -106,16 +367,45 @@ impl Window {
             self.h = size.1;
             self.t = title.to_string();
         }
+
+        // The window surface follows the window size, but the buffer of an accelerated window must be replaced
+        let resized = match self.buffer {
+            Some((ref surface, _)) => surface.width() != self.w || surface.height() != self.h,
+            None => false
+        };
+        if resized {
+            if let Some(buffer) = Window::create_buffer(&self.inner, self.w, self.h) {
+                self.buffer = Some(buffer);
+            }
+        }
+    }
+
+    /// Flip only a region of the window buffer
+    pub fn sync_rect(&mut self, x: i32, y: i32, w: u32, h: u32) -> bool {
+        let x1 = cmp::max(0, x);
+        let y1 = cmp::max(0, y);
+        let x2 = cmp::min(self.w as i32, x + w as i32);
+        let y2 = cmp::min(self.h as i32, y + h as i32);
+
+        if x1 >= x2 || y1 >= y2 {
+            true
+        } else if self.buffer.is_some() || (x1 == 0 && y1 == 0 && x2 == self.w as i32 && y2 == self.h as i32) {
+            self.sync()
+        } else {
+            self.wait_frame();
+            match self.inner.window() {
+                Some(window) => window.update_surface_rects(&[sdl2::rect::Rect::new(x1, y1, (x2 - x1) as u32, (y2 - y1) as u32)]).is_ok(),
+                None => true
+            }
+        }
     }
 
     /// Get x
-    // TODO: Sync with window movements
     pub fn x(&self) -> i32 {
         self.x
     }
 
     /// Get y
-    // TODO: Sync with window movements
     pub fn y(&self) -> i32 {
         self.y
     }
@@ -125,7 +415,33 @@ impl Window {
         self.t.clone()
     }
 
-    // Set position
+    /// Get the scale of the display the window is on, relative to a standard 96 DPI display
+    pub fn scale(&self) -> f32 {
+        if let Some(window) = self.inner.window() {
+            if let Ok(index) = window.display_index() {
+                if let Ok((dpi, _, _)) = unsafe { & *VIDEO_CTX }.display_dpi(index) {
+                    if dpi > 0.0 {
+                        return dpi / 96.0;
+                    }
+                }
+            }
+        }
+        1.0
+    }
+
+    /// Get the position and size of the display the window is on
+    pub fn display_bounds(&self) -> Option<(i32, i32, u32, u32)> {
+        if let Some(window) = self.inner.window() {
+            if let Ok(index) = window.display_index() {
+                if let Ok(rect) = unsafe { & *VIDEO_CTX }.display_bounds(index) {
+                    return Some((rect.x(), rect.y(), rect.width(), rect.height()));
+                }
+            }
+        }
+        None
+    }
+
+    /// Set position
     pub fn set_pos(&mut self, x: i32, y: i32) {
         if let Some(mut window) = self.inner.window_mut() {
             let _ = window.set_position(sdl2::video::WindowPos::Positioned(x),
@@ -134,7 +450,7 @@ impl Window {
         self.sync_path();
     }
 
-    // Set size
+    /// Set size
     pub fn set_size(&mut self, width: u32, height: u32) {
         if let Some(mut window) = self.inner.window_mut() {
             let _ = window.set_size(width, height);
@@ -142,43 +458,335 @@ impl Window {
         self.sync_path();
     }
 
-    /// Set title
+    /// Get the mouse position relative to the window and whether the left, middle and right buttons are pressed
+    pub fn mouse(&self) -> (i32, i32, bool, bool, bool) {
+        if self.inner.window().is_none() {
+            return (0, 0, false, false, false);
+        }
+
+        let mouse = unsafe { & *EVENT_PUMP }.mouse_state();
+        (mouse.x(), mouse.y(), mouse.left(), mouse.middle(), mouse.right())
+    }
+
+    /// Check if a key is currently held down. Any `K_*` scancode that `KeyEvent` reports is supported
+    pub fn key_pressed(&self, scancode: u8) -> bool {
+        if self.inner.window().is_none() {
+            return false;
+        }
+
+        unsafe { & *EVENT_PUMP }.keyboard_state().pressed_scancodes().any(|pressed| {
+            Window::convert_scancode(Some(pressed), false, false, true).map_or(false, |code| code.1 == scancode)
+        })
+    }
+
+    /// Lock the pixel buffer once and pass it to `f` along with the row stride in pixels
+    pub fn with_pixels<F: FnMut(&mut [Color], usize)>(&mut self, mut f: F) {
+        let surface = self.surface_mut();
+        let stride = surface.pitch() as usize / mem::size_of::<Color>();
+        surface.with_lock_mut(|bytes| {
+            f(unsafe { slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut Color, bytes.len()/mem::size_of::<Color>()) }, stride)
+        });
+    }
+
+    /// Take the path of the oldest dropped file, announced by a `DropEvent`
+    pub fn pop_drop_path(&mut self) -> Option<String> {
+        self.drop_paths.pop_front()
+    }
+
+    /// Set how many milliseconds apart two presses can be to count as a double click
+    pub fn set_double_click_ms(&mut self, ms: u32) {
+        self.double_click_ms = ms;
+    }
+
+    /// Limit how many times per second `sync` presents, sleeping for the rest of each frame. Passing 0 removes the limit
+    pub fn set_frame_rate(&mut self, fps: u32) {
+        self.frame_interval = if fps > 0 {
+            Some(Duration::new(0, 1_000_000_000 / fps))
+        } else {
+            None
+        };
+    }
+
+    /// Sleep until the current frame has lasted the frame interval, then start the next one
+    fn wait_frame(&mut self) {
+        if let (Some(interval), Some(last)) = (self.frame_interval, self.last_frame) {
+            let elapsed = last.elapsed();
+            if elapsed < interval {
+                thread::sleep(interval - elapsed);
+            }
+        }
+        self.last_frame = Some(Instant::now());
+    }
+
+    /// Record a button press, returning a double click event if it completes one
+    fn double_click(&mut self, x: i32, y: i32, button: u8) -> Option<Event> {
+        let now = Instant::now();
+        if let Some((time, last_x, last_y, last_button)) = self.last_click.take() {
+            let quick = now.duration_since(time) <= Duration::from_millis(self.double_click_ms as u64);
+            let near = (x - last_x).abs() <= DOUBLE_CLICK_DISTANCE && (y - last_y).abs() <= DOUBLE_CLICK_DISTANCE;
+            if button == last_button && quick && near {
+                return Some(MouseDoubleClickEvent {
+                    x: x,
+                    y: y,
+                    button: button
+                }.to_event());
+            }
+        }
+        self.last_click = Some((now, x, y, button));
+        None
+    }
+
+    /// Show or hide the mouse cursor
+    pub fn set_mouse_cursor(&mut self, visible: bool) {
+        if self.inner.window().is_some() {
+            unsafe { & *SDL_CTX }.mouse().show_cursor(visible);
+        }
+    }
+
+    /// Set the shape of the mouse cursor while it is over the window
+    pub fn set_cursor(&mut self, style: CursorStyle) {
+        if self.inner.window().is_none() {
+            return;
+        }
+
+        let system_cursor = match style {
+            CursorStyle::Arrow => sdl2::mouse::SystemCursor::Arrow,
+            CursorStyle::Hand => sdl2::mouse::SystemCursor::Hand,
+            CursorStyle::IBeam => sdl2::mouse::SystemCursor::IBeam,
+            CursorStyle::Crosshair => sdl2::mouse::SystemCursor::Crosshair,
+            CursorStyle::Wait => sdl2::mouse::SystemCursor::Wait,
+            CursorStyle::SizeNWSE => sdl2::mouse::SystemCursor::SizeNWSE,
+            CursorStyle::SizeNESW => sdl2::mouse::SystemCursor::SizeNESW,
+            CursorStyle::SizeWE => sdl2::mouse::SystemCursor::SizeWE,
+            CursorStyle::SizeNS => sdl2::mouse::SystemCursor::SizeNS,
+            CursorStyle::SizeAll => sdl2::mouse::SystemCursor::SizeAll,
+            CursorStyle::No => sdl2::mouse::SystemCursor::No,
+        };
+
+        if let Ok(cursor) = sdl2::mouse::Cursor::from_system(system_cursor) {
+            cursor.set();
+            self.cursor = Some(cursor);
+        }
+    }
+
+    /// Move the mouse cursor to a position relative to the window
+    pub fn set_mouse_pos(&mut self, x: i32, y: i32) {
+        if let Some(window) = self.inner.window() {
+            unsafe { & *SDL_CTX }.mouse().warp_mouse_in_window(window, x, y);
+        }
+    }
+
+    /// Enable or disable relative mouse mode, which hides the cursor and reports motion without limits
+    pub fn set_mouse_relative(&mut self, relative: bool) {
+        if self.inner.window().is_some() {
+            unsafe { & *SDL_CTX }.mouse().set_relative_mouse_mode(relative);
+        }
+    }
+
+    /// Set the fullscreen mode
+    pub fn set_fullscreen(&mut self, mode: FullscreenMode) {
+        if let Some(window) = self.inner.window_mut() {
+            let _ = window.set_fullscreen(match mode {
+                FullscreenMode::Off => sdl2::video::FullscreenType::Off,
+                FullscreenMode::Desktop => sdl2::video::FullscreenType::Desktop,
+                FullscreenMode::Exclusive => sdl2::video::FullscreenType::True,
+            });
+        }
+        self.sync_path();
+    }
+
+    /// Set the opacity of the whole window, from 0.0 for invisible to 1.0 for opaque. Returns false if it is not supported, including on SDL older than 2.0.5
+    pub fn set_opacity(&mut self, opacity: f32) -> bool {
+        let opacity = opacity.max(0.0).min(1.0);
+        let set_window_opacity = unsafe { function(b"SDL_SetWindowOpacity\0") };
+        if set_window_opacity.is_null() {
+            return false;
+        }
+
+        let set_window_opacity: SetWindowOpacity = unsafe { mem::transmute(set_window_opacity) };
+        match self.inner.window() {
+            Some(window) => set_window_opacity(window.raw() as *mut c_void, opacity) == 0,
+            None => false
+        }
+    }
+
+    /// Minimize the window
+    pub fn minimize(&mut self) {
+        if let Some(window) = self.inner.window_mut() {
+            window.minimize();
+        }
+        self.sync_path();
+    }
+
+    /// Maximize the window
+    pub fn maximize(&mut self) {
+        if let Some(window) = self.inner.window_mut() {
+            window.maximize();
+        }
+        self.sync_path();
+    }
+
+    /// Restore the window from being minimized or maximized
+    pub fn restore(&mut self) {
+        if let Some(window) = self.inner.window_mut() {
+            window.restore();
+        }
+        self.sync_path();
+    }
+
+    /// Keep the window above other windows, or stop doing so. Returns false if the window is not in the requested state afterwards, including on SDL older than 2.0.16 which can only set this with `WindowFlag::Front`
+    pub fn set_always_on_top(&mut self, on: bool) -> bool {
+        let set_window_always_on_top = unsafe { function(b"SDL_SetWindowAlwaysOnTop\0") };
+        match self.inner.window() {
+            Some(window) => {
+                if ! set_window_always_on_top.is_null() {
+                    let set_window_always_on_top: SetWindowAlwaysOnTop = unsafe { mem::transmute(set_window_always_on_top) };
+                    set_window_always_on_top(window.raw() as *mut c_void, on as c_int);
+                }
+                (window.window_flags() & SDL_WINDOW_ALWAYS_ON_TOP != 0) == on
+            },
+            None => false
+        }
+    }
+
+    /// Turn vsync on or off. SDL only takes this when creating a renderer, so the renderer is recreated, keeping what has been drawn. Returns false if the renderer does not support the requested state, or if no renderer could be created, which closes the window
+    pub fn set_vsync(&mut self, on: bool) -> bool {
+        let flags = self.inner.info().flags;
+        if (flags & SDL_RENDERER_PRESENTVSYNC != 0) == on {
+            return true;
+        } else if self.inner.window().is_none() {
+            return false;
+        }
+
+        // The renderer is swapped for an offscreen one while the window is moved to a new one, which is kept if that fails
+        let placeholder = match sdl2::surface::Surface::new(self.w, self.h, sdl2::pixels::PixelFormatEnum::ARGB8888) {
+            Ok(surface) => match sdl2::render::Renderer::from_surface(surface) {
+                Ok(placeholder) => placeholder,
+                Err(_) => return false
+            },
+            Err(_) => return false
+        };
+        let window = match mem::replace(&mut self.inner, placeholder).into_window() {
+            Some(window) => window,
+            None => return false
+        };
+
+        let new_flags = if on {
+            flags | SDL_RENDERER_PRESENTVSYNC
+        } else {
+            flags & ! SDL_RENDERER_PRESENTVSYNC
+        };
+        let (w, h) = (self.w, self.h);
+        let texture = self.buffer.is_some();
+        let created = Window::create_renderer(window, new_flags, texture, w, h)
+            .or_else(|window| Window::create_renderer(window, flags, texture, w, h))
+            .or_else(|window| Window::create_renderer(window, SDL_RENDERER_SOFTWARE, texture, w, h));
+
+        match created {
+            Ok((inner, texture)) => {
+                self.inner = inner;
+                // The surfaces outlive the renderer, but the texture of an accelerated window must be replaced
+                if let (Some((surface, _)), Some(texture)) = (self.buffer.take(), texture) {
+                    self.buffer = Some((surface, texture));
+                }
+            },
+            Err(_) => {
+                // The window is gone, leaving the offscreen renderer, so the window counts as closed
+                self.buffer = None;
+                self.closing = true;
+                return false;
+            }
+        }
+
+        (self.inner.info().flags & SDL_RENDERER_PRESENTVSYNC != 0) == on
+    }
+
+    /// Set the window icon from pixel data, row by row from the top left. Nothing happens if `data` is not `w * h` long
+    pub fn set_icon(&mut self, w: u32, h: u32, data: &[Color]) {
+        if data.len() != w as usize * h as usize {
+            return;
+        }
+
+        if let Ok(mut surface) = sdl2::surface::Surface::new(w, h, sdl2::pixels::PixelFormatEnum::ARGB8888) {
+            let pitch = surface.pitch() as usize;
+            surface.with_lock_mut(|bytes| {
+                for (y, row) in data.chunks(w as usize).enumerate() {
+                    for (x, color) in row.iter().enumerate() {
+                        let i = y * pitch + x * 4;
+                        let value = color.data;
+                        bytes[i] = value as u8;
+                        bytes[i + 1] = (value >> 8) as u8;
+                        bytes[i + 2] = (value >> 16) as u8;
+                        bytes[i + 3] = (value >> 24) as u8;
+                    }
+                }
+            });
+
+            if let Some(window) = self.inner.window_mut() {
+                window.set_icon(surface);
+            }
+        }
+    }
+
+    /// Set the window icon from a bitmap
+    pub fn set_icon_bmp(&mut self, bmp: &BmpFile) {
+        self.set_icon(bmp.width(), bmp.height(), bmp.data());
+    }
+
+    /// Set the clipping rectangle, or clear it with `None`
+    pub fn set_clip(&mut self, rect: Option<(i32, i32, u32, u32)>) {
+        self.clip = rect;
+    }
+
+    /// Draw text with a custom bitmap font, which holds a glyph of `glyph_height` rows for each character, each row being one byte for 8 pixels. An empty font or a height of 0 goes back to the built-in font
+    pub fn set_font(&mut self, font: &[u8], glyph_height: usize) {
+        self.font = if font.is_empty() || glyph_height == 0 {
+            None
+        } else {
+            Some((font.to_vec(), glyph_height))
+        };
+    }
+
+    /// Set title. SDL takes titles as UTF-8, so any Unicode title is kept exactly, except that one containing a null character is ignored
     pub fn set_title(&mut self, title: &str) {
         if let Some(mut window) = self.inner.window_mut() {
             let _ = window.set_title(title);
+        } else if ! title.contains('\0') {
+            // An offscreen window has no SDL window to hold the title
+            self.t = title.to_string();
         }
         self.sync_path();
     }
 
-    fn convert_scancode(&self, scancode_option: Option<sdl2::keyboard::Scancode>, shift: bool) -> Option<(char, u8)> {
+    fn convert_scancode(scancode_option: Option<sdl2::keyboard::Scancode>, shift: bool, caps: bool, num: bool) -> Option<(char, u8)> {
         if let Some(scancode) = scancode_option {
             match scancode {
-                sdl2::keyboard::Scancode::A => Some((if shift { 'A' } else { 'a' }, K_A)),
-                sdl2::keyboard::Scancode::B => Some((if shift { 'B' } else { 'b' }, K_B)),
-                sdl2::keyboard::Scancode::C => Some((if shift { 'C' } else { 'c' }, K_C)),
-                sdl2::keyboard::Scancode::D => Some((if shift { 'D' } else { 'd' }, K_D)),
-                sdl2::keyboard::Scancode::E => Some((if shift { 'E' } else { 'e' }, K_E)),
-                sdl2::keyboard::Scancode::F => Some((if shift { 'F' } else { 'f' }, K_F)),
-                sdl2::keyboard::Scancode::G => Some((if shift { 'G' } else { 'g' }, K_G)),
-                sdl2::keyboard::Scancode::H => Some((if shift { 'H' } else { 'h' }, K_H)),
-                sdl2::keyboard::Scancode::I => Some((if shift { 'I' } else { 'i' }, K_I)),
-                sdl2::keyboard::Scancode::J => Some((if shift { 'J' } else { 'j' }, K_J)),
-                sdl2::keyboard::Scancode::K => Some((if shift { 'K' } else { 'k' }, K_K)),
-                sdl2::keyboard::Scancode::L => Some((if shift { 'L' } else { 'l' }, K_L)),
-                sdl2::keyboard::Scancode::M => Some((if shift { 'M' } else { 'm' }, K_M)),
-                sdl2::keyboard::Scancode::N => Some((if shift { 'N' } else { 'n' }, K_N)),
-                sdl2::keyboard::Scancode::O => Some((if shift { 'O' } else { 'o' }, K_O)),
-                sdl2::keyboard::Scancode::P => Some((if shift { 'P' } else { 'p' }, K_P)),
-                sdl2::keyboard::Scancode::Q => Some((if shift { 'Q' } else { 'q' }, K_Q)),
-                sdl2::keyboard::Scancode::R => Some((if shift { 'R' } else { 'r' }, K_R)),
-                sdl2::keyboard::Scancode::S => Some((if shift { 'S' } else { 's' }, K_S)),
-                sdl2::keyboard::Scancode::T => Some((if shift { 'T' } else { 't' }, K_T)),
-                sdl2::keyboard::Scancode::U => Some((if shift { 'U' } else { 'u' }, K_U)),
-                sdl2::keyboard::Scancode::V => Some((if shift { 'V' } else { 'v' }, K_V)),
-                sdl2::keyboard::Scancode::W => Some((if shift { 'W' } else { 'w' }, K_W)),
-                sdl2::keyboard::Scancode::X => Some((if shift { 'X' } else { 'x' }, K_X)),
-                sdl2::keyboard::Scancode::Y => Some((if shift { 'Y' } else { 'y' }, K_Y)),
-                sdl2::keyboard::Scancode::Z => Some((if shift { 'Z' } else { 'z' }, K_Z)),
+                sdl2::keyboard::Scancode::A => Some((if shift != caps { 'A' } else { 'a' }, K_A)),
+                sdl2::keyboard::Scancode::B => Some((if shift != caps { 'B' } else { 'b' }, K_B)),
+                sdl2::keyboard::Scancode::C => Some((if shift != caps { 'C' } else { 'c' }, K_C)),
+                sdl2::keyboard::Scancode::D => Some((if shift != caps { 'D' } else { 'd' }, K_D)),
+                sdl2::keyboard::Scancode::E => Some((if shift != caps { 'E' } else { 'e' }, K_E)),
+                sdl2::keyboard::Scancode::F => Some((if shift != caps { 'F' } else { 'f' }, K_F)),
+                sdl2::keyboard::Scancode::G => Some((if shift != caps { 'G' } else { 'g' }, K_G)),
+                sdl2::keyboard::Scancode::H => Some((if shift != caps { 'H' } else { 'h' }, K_H)),
+                sdl2::keyboard::Scancode::I => Some((if shift != caps { 'I' } else { 'i' }, K_I)),
+                sdl2::keyboard::Scancode::J => Some((if shift != caps { 'J' } else { 'j' }, K_J)),
+                sdl2::keyboard::Scancode::K => Some((if shift != caps { 'K' } else { 'k' }, K_K)),
+                sdl2::keyboard::Scancode::L => Some((if shift != caps { 'L' } else { 'l' }, K_L)),
+                sdl2::keyboard::Scancode::M => Some((if shift != caps { 'M' } else { 'm' }, K_M)),
+                sdl2::keyboard::Scancode::N => Some((if shift != caps { 'N' } else { 'n' }, K_N)),
+                sdl2::keyboard::Scancode::O => Some((if shift != caps { 'O' } else { 'o' }, K_O)),
+                sdl2::keyboard::Scancode::P => Some((if shift != caps { 'P' } else { 'p' }, K_P)),
+                sdl2::keyboard::Scancode::Q => Some((if shift != caps { 'Q' } else { 'q' }, K_Q)),
+                sdl2::keyboard::Scancode::R => Some((if shift != caps { 'R' } else { 'r' }, K_R)),
+                sdl2::keyboard::Scancode::S => Some((if shift != caps { 'S' } else { 's' }, K_S)),
+                sdl2::keyboard::Scancode::T => Some((if shift != caps { 'T' } else { 't' }, K_T)),
+                sdl2::keyboard::Scancode::U => Some((if shift != caps { 'U' } else { 'u' }, K_U)),
+                sdl2::keyboard::Scancode::V => Some((if shift != caps { 'V' } else { 'v' }, K_V)),
+                sdl2::keyboard::Scancode::W => Some((if shift != caps { 'W' } else { 'w' }, K_W)),
+                sdl2::keyboard::Scancode::X => Some((if shift != caps { 'X' } else { 'x' }, K_X)),
+                sdl2::keyboard::Scancode::Y => Some((if shift != caps { 'Y' } else { 'y' }, K_Y)),
+                sdl2::keyboard::Scancode::Z => Some((if shift != caps { 'Z' } else { 'z' }, K_Z)),
                 sdl2::keyboard::Scancode::Num0 => Some((if shift { ')' } else { '0' }, K_0)),
                 sdl2::keyboard::Scancode::Num1 => Some((if shift { '!' } else { '1' }, K_1)),
                 sdl2::keyboard::Scancode::Num2 => Some((if shift { '@' } else { '2' }, K_2)),
@@ -230,6 +838,22 @@ impl Window {
                 sdl2::keyboard::Scancode::Delete => Some(('\0', K_DEL)),
                 sdl2::keyboard::Scancode::F11 => Some(('\0', K_F11)),
                 sdl2::keyboard::Scancode::F12 => Some(('\0', K_F12)),
+                sdl2::keyboard::Scancode::Kp0 => Some((if num { '0' } else { '\0' }, K_NUM_0)),
+                sdl2::keyboard::Scancode::Kp1 => Some((if num { '1' } else { '\0' }, K_NUM_1)),
+                sdl2::keyboard::Scancode::Kp2 => Some((if num { '2' } else { '\0' }, K_NUM_2)),
+                sdl2::keyboard::Scancode::Kp3 => Some((if num { '3' } else { '\0' }, K_NUM_3)),
+                sdl2::keyboard::Scancode::Kp4 => Some((if num { '4' } else { '\0' }, K_NUM_4)),
+                sdl2::keyboard::Scancode::Kp5 => Some((if num { '5' } else { '\0' }, K_NUM_5)),
+                sdl2::keyboard::Scancode::Kp6 => Some((if num { '6' } else { '\0' }, K_NUM_6)),
+                sdl2::keyboard::Scancode::Kp7 => Some((if num { '7' } else { '\0' }, K_NUM_7)),
+                sdl2::keyboard::Scancode::Kp8 => Some((if num { '8' } else { '\0' }, K_NUM_8)),
+                sdl2::keyboard::Scancode::Kp9 => Some((if num { '9' } else { '\0' }, K_NUM_9)),
+                sdl2::keyboard::Scancode::KpPeriod => Some((if num { '.' } else { '\0' }, K_NUM_PERIOD)),
+                sdl2::keyboard::Scancode::KpPlus => Some(('+', K_NUM_PLUS)),
+                sdl2::keyboard::Scancode::KpMinus => Some(('-', K_NUM_MINUS)),
+                sdl2::keyboard::Scancode::KpMultiply => Some(('*', K_NUM_ASTERISK)),
+                sdl2::keyboard::Scancode::KpDivide => Some(('/', K_SLASH)),
+                sdl2::keyboard::Scancode::KpEnter => Some(('\n', K_ENTER)),
                 sdl2::keyboard::Scancode::LShift => Some(('\0', K_LEFT_SHIFT)),
                 sdl2::keyboard::Scancode::RShift => Some(('\0', K_RIGHT_SHIFT)),
                 _ => None
@@ -253,16 +877,22 @@ impl Window {
             }.to_event()
         };
 
-        let mods = unsafe { &mut *SDL_CTX }.keyboard().mod_state();
-        let shift = if mods.contains(sdl2::keyboard::CAPSMOD)
-                    || mods.contains(sdl2::keyboard::LSHIFTMOD)
-                    || mods.contains(sdl2::keyboard::RSHIFTMOD)
-        {
-            true
-        } else {
-            false
+        let button_event = |button: sdl2::mouse::MouseButton, pressed: bool| -> Event {
+            let mouse = unsafe { &mut *EVENT_PUMP }.mouse_state();
+            ButtonEvent {
+                left: if button == sdl2::mouse::MouseButton::Left { pressed } else { mouse.left() },
+                middle: if button == sdl2::mouse::MouseButton::Middle { pressed } else { mouse.middle() },
+                right: if button == sdl2::mouse::MouseButton::Right { pressed } else { mouse.right() }
+            }.to_event()
         };
 
+        let mods = unsafe { &mut *SDL_CTX }.keyboard().mod_state();
+        let shift = mods.contains(sdl2::keyboard::LSHIFTMOD) || mods.contains(sdl2::keyboard::RSHIFTMOD);
+        let caps = mods.contains(sdl2::keyboard::CAPSMOD);
+        let num = mods.contains(sdl2::keyboard::NUMMOD);
+        let ctrl = mods.contains(sdl2::keyboard::LCTRLMOD) || mods.contains(sdl2::keyboard::RCTRLMOD);
+        let alt = mods.contains(sdl2::keyboard::LALTMOD) || mods.contains(sdl2::keyboard::RALTMOD);
+
         match event {
             sdl2::event::Event::Window { win_event, .. } => match win_event {
                 sdl2::event::WindowEvent::Moved(x, y) => events.push(MoveEvent {
@@ -279,25 +909,82 @@ impl Window {
                 sdl2::event::WindowEvent::FocusLost => events.push(FocusEvent {
                     focused: false
                 }.to_event()),
+                sdl2::event::WindowEvent::Enter => events.push(HoverEvent {
+                    entered: true
+                }.to_event()),
+                sdl2::event::WindowEvent::Leave => events.push(HoverEvent {
+                    entered: false
+                }.to_event()),
                 _ => ()
             },
-            sdl2::event::Event::MouseMotion { .. } => events.push(mouse_event()),
-            sdl2::event::Event::MouseButtonDown { .. } => events.push(mouse_event()),
-            sdl2::event::Event::MouseButtonUp { .. } => events.push(mouse_event()),
-            sdl2::event::Event::KeyDown { scancode, .. } => if let Some(code) = self.convert_scancode(scancode, shift) {
+            sdl2::event::Event::MouseMotion { xrel, yrel, .. } => {
+                events.push(mouse_event());
+                events.push(MouseRelativeEvent {
+                    dx: xrel,
+                    dy: yrel
+                }.to_event());
+            },
+            sdl2::event::Event::MouseButtonDown { mouse_btn, .. } => {
+                events.push(mouse_event());
+                events.push(button_event(mouse_btn, true));
+            },
+            sdl2::event::Event::MouseButtonUp { mouse_btn, .. } => {
+                events.push(mouse_event());
+                events.push(button_event(mouse_btn, false));
+            },
+            sdl2::event::Event::MouseWheel { x, y, .. } => events.push(ScrollEvent {
+                x: x,
+                y: y
+            }.to_event()),
+            sdl2::event::Event::KeyDown { scancode: Some(scancode), .. } => {
+                let code = Window::convert_scancode(Some(scancode), shift, caps, num).unwrap_or(('\0', 0));
                 events.push(KeyEvent {
                     character: code.0,
                     scancode: code.1,
-                    pressed: true
+                    pressed: true,
+                    shift: shift,
+                    ctrl: ctrl,
+                    alt: alt,
+                    raw_scancode: scancode as i32
                 }.to_event());
             },
-            sdl2::event::Event::KeyUp { scancode, .. } => if let Some(code) = self.convert_scancode(scancode, shift) {
+            sdl2::event::Event::KeyUp { scancode: Some(scancode), .. } => {
+                let code = Window::convert_scancode(Some(scancode), shift, caps, num).unwrap_or(('\0', 0));
                 events.push(KeyEvent {
                     character: code.0,
                     scancode: code.1,
-                    pressed: false
+                    pressed: false,
+                    shift: shift,
+                    ctrl: ctrl,
+                    alt: alt,
+                    raw_scancode: scancode as i32
+                }.to_event());
+            },
+            sdl2::event::Event::TextInput { text, .. } => for character in text.chars() {
+                events.push(TextInputEvent {
+                    character: character
                 }.to_event());
             },
+            sdl2::event::Event::ControllerButtonDown { which, button, .. } => events.push(ControllerEvent {
+                id: which,
+                button: button as u8,
+                pressed: true
+            }.to_event()),
+            sdl2::event::Event::ControllerButtonUp { which, button, .. } => events.push(ControllerEvent {
+                id: which,
+                button: button as u8,
+                pressed: false
+            }.to_event()),
+            sdl2::event::Event::ControllerAxisMotion { which, axis, value, .. } => events.push(ControllerAxisEvent {
+                id: which,
+                axis: axis as u8,
+                value: value
+            }.to_event()),
+            sdl2::event::Event::ControllerDeviceRemoved { which, .. } => events.push(ControllerDeviceEvent {
+                id: which,
+                connected: false
+            }.to_event()),
+            sdl2::event::Event::DropFile { .. } => events.push(DropEvent.to_event()),
             sdl2::event::Event::Quit { .. } => events.push(QuitEvent.to_event()),
             _ => (),
         }
@@ -305,44 +992,116 @@ impl Window {
         events
     }
 
+    /// Check if the window is still open, which stops being true once a quit event arrives or `close` is called
+    pub fn is_open(&self) -> bool {
+        ! self.closing
+    }
+
+    /// Request that the window close
+    pub fn close(&mut self) {
+        self.closing = true;
+    }
+
+    /// Call `f` for every event until it returns false or the window closes. A quit event is still passed to `f`
+    pub fn run<F: FnMut(&mut Window, Event) -> bool>(&mut self, mut f: F) {
+        loop {
+            let iter = self.events();
+            // A quit event marks the window closing as soon as it is read, so only stop early for one that `f` has seen
+            let quit_read = iter.events.iter().any(|event| event.code == EVENT_QUIT);
+            for event in iter {
+                let quit = event.code == EVENT_QUIT;
+                if ! f(self, event) || quit || (! quit_read && ! self.is_open()) {
+                    return;
+                }
+            }
+        }
+    }
+
     /// Blocking iterator over events
     pub fn events(&mut self) -> EventIter {
+        let event_option = if ! self.async && self.inner.window().is_some() {
+            Some(unsafe { &mut *EVENT_PUMP }.wait_event())
+        } else {
+            None
+        };
+        self.collect_events(event_option)
+    }
+
+    /// Take every pending event without waiting, however many there are
+    pub fn poll_events(&mut self) -> Vec<Event> {
+        self.collect_events(None).events
+    }
+
+    /// Iterator over events, waiting at most `ms` milliseconds for the first one
+    pub fn events_timeout(&mut self, ms: u32) -> EventIter {
+        let event_option = if ms > 0 && self.inner.window().is_some() {
+            unsafe { &mut *EVENT_PUMP }.wait_event_timeout(ms)
+        } else {
+            None
+        };
+        self.collect_events(event_option)
+    }
+
+    /// Update the window state that an event affects, then convert it
+    fn process_event(&mut self, event: sdl2::event::Event) -> Vec<Event> {
+        match event {
+            sdl2::event::Event::Window { .. } => self.sync_path(),
+            // Controllers must be opened to send events. Added events give a device index, but all later events give the instance id
+            sdl2::event::Event::ControllerDeviceAdded { which, .. } => {
+                if let Some(controller_ctx) = unsafe { CONTROLLER_CTX.as_ref() } {
+                    if let Ok(controller) = controller_ctx.open(which as u32) {
+                        let id = controller.instance_id();
+                        self.controllers.push(controller);
+                        return vec![ControllerDeviceEvent {
+                            id: id,
+                            connected: true
+                        }.to_event()];
+                    }
+                }
+            },
+            sdl2::event::Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.retain(|controller| controller.instance_id() != which);
+            },
+            sdl2::event::Event::DropFile { ref filename, .. } => self.drop_paths.push_back(filename.clone()),
+            _ => ()
+        }
+
+        let double_click = match event {
+            sdl2::event::Event::MouseButtonDown { mouse_btn, x, y, .. } => match mouse_btn {
+                sdl2::mouse::MouseButton::Left => self.double_click(x, y, BUTTON_LEFT),
+                sdl2::mouse::MouseButton::Middle => self.double_click(x, y, BUTTON_MIDDLE),
+                sdl2::mouse::MouseButton::Right => self.double_click(x, y, BUTTON_RIGHT),
+                _ => None
+            },
+            _ => None
+        };
+
+        let mut events = self.convert_event(event);
+        events.extend(double_click);
+        events
+    }
+
+    fn collect_events(&mut self, event_option: Option<sdl2::event::Event>) -> EventIter {
         let mut iter = EventIter {
-            events: [Event::new(); 128],
+            events: Vec::new(),
             i: 0,
-            count: 0,
         };
 
-        if ! self.async {
-            let event = unsafe { &mut *EVENT_PUMP }.wait_event();
-            if let sdl2::event::Event::Window{..} = event {
-                self.sync_path();
-            }
-            for converted_event in self.convert_event(event) {
-                if iter.count < iter.events.len() {
-                    iter.events[iter.count] = converted_event;
-                    iter.count += 1;
-                } else {
-                    break;
-                }
-            }
+        // Offscreen windows do not receive events
+        if self.inner.window().is_none() {
+            return iter;
+        }
+
+        if let Some(event) = event_option {
+            iter.events.extend(self.process_event(event));
         }
 
         while let Some(event) = unsafe { &mut *EVENT_PUMP }.poll_event() {
-            if let sdl2::event::Event::Window{..} = event {
-                self.sync_path();
-            }
-            for converted_event in self.convert_event(event) {
-                if iter.count < iter.events.len() {
-                    iter.events[iter.count] = converted_event;
-                    iter.count += 1;
-                } else {
-                    break;
-                }
-            }
-            if iter.count + 2 < iter.events.len() {
-                break;
-            }
+            iter.events.extend(self.process_event(event));
+        }
+
+        if iter.events.iter().any(|event| event.code == EVENT_QUIT) {
+            self.closing = true;
         }
 
         iter
@@ -351,23 +1110,105 @@ impl Window {
 
 /// Event iterator
 pub struct EventIter {
-    events: [Event; 128],
+    events: Vec<Event>,
     i: usize,
-    count: usize,
 }
 
 impl Iterator for EventIter {
     type Item = Event;
     fn next(&mut self) -> Option<Event> {
-        if self.i < self.count {
-            if let Some(event) = self.events.get(self.i) {
-                self.i += 1;
-                Some(*event)
-            } else {
-                None
-            }
+        if let Some(event) = self.events.get(self.i) {
+            self.i += 1;
+            Some(*event)
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::sync::{Once, ONCE_INIT};
+
+    use event::{ScrollEvent, EVENT_SCROLL};
+    use renderer::Renderer;
+    use super::{init, SDL_CTX};
+    use super::sdl2::event::Event;
+    use super::sdl2::keyboard::Scancode;
+    use super::sdl2::mouse::MouseWheelDirection;
+    use super::Window;
+
+    /// Open a window on SDL's dummy video driver, so tests run without a display
+    fn dummy_window(w: u32, h: u32) -> Window {
+        static INIT: Once = ONCE_INIT;
+        INIT.call_once(|| {
+            env::set_var("SDL_VIDEODRIVER", "dummy");
+            unsafe { init() };
+        });
+        Window::new(0, 0, w, h, "test").unwrap()
+    }
+
+    #[test]
+    fn convert_scancode_modifiers() {
+        // shift, caps, num, then the characters of A, 1, semicolon and keypad 1
+        let table = [
+            (false, false, false, ['a', '1', ';', '\0']),
+            (true, false, false, ['A', '!', ':', '\0']),
+            (false, true, false, ['A', '1', ';', '\0']),
+            (true, true, false, ['a', '!', ':', '\0']),
+            (false, false, true, ['a', '1', ';', '1']),
+            (true, false, true, ['A', '!', ':', '1']),
+            (false, true, true, ['A', '1', ';', '1']),
+            (true, true, true, ['a', '!', ':', '1']),
+        ];
+        let scancodes = [Scancode::A, Scancode::Num1, Scancode::Semicolon, Scancode::Kp1];
+
+        for &(shift, caps, num, characters) in table.iter() {
+            for (&scancode, &character) in scancodes.iter().zip(characters.iter()) {
+                let code = Window::convert_scancode(Some(scancode), shift, caps, num).map(|code| code.0);
+                assert_eq!(code, Some(character), "{:?} with shift {} caps {} num {}", scancode, shift, caps, num);
+            }
+        }
+    }
+
+    #[test]
+    fn convert_scancode_unmapped() {
+        assert_eq!(Window::convert_scancode(None, false, false, false), None);
+        assert_eq!(Window::convert_scancode(Some(Scancode::Application), false, false, false), None);
+    }
+
+    #[test]
+    fn set_size_resizes_data() {
+        let mut window = dummy_window(64, 48);
+        assert_eq!(window.data().len(), 64 * 48);
+
+        window.set_size(100, 30);
+        assert_eq!((window.width(), window.height()), (100, 30));
+        assert_eq!(window.data().len(), 100 * 30);
+    }
+
+    #[test]
+    fn events_delivers_every_queued_event() {
+        let mut window = dummy_window(32, 32);
+        window.poll_events();
+
+        let event_subsystem = unsafe { & *SDL_CTX }.event().unwrap();
+        for i in 0..200 {
+            event_subsystem.push_event(Event::MouseWheel {
+                timestamp: 0,
+                window_id: 0,
+                which: 0,
+                x: 0,
+                y: i,
+                direction: MouseWheelDirection::Normal
+            }).unwrap();
+        }
+
+        let scrolls: Vec<i32> = window.events()
+            .filter(|event| event.code == EVENT_SCROLL)
+            .map(|event| ScrollEvent::from_event(event).y)
+            .collect();
+        assert_eq!(scrolls, (0..200).collect::<Vec<i32>>());
+    }
+}